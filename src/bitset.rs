@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use maze::{Coord, Maze};
+use topology::Topology;
+
+const WORD_BITS: usize = 64;
+
+/// A fixed size set of bits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> BitSet {
+        BitSet {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> bool {
+        debug_assert!(idx < self.len);
+        self.words[idx / WORD_BITS] & (1 << (idx % WORD_BITS)) != 0
+    }
+
+    /// Sets the bit at `idx`, returning its previous value
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: bool) -> bool {
+        debug_assert!(idx < self.len);
+        let word = &mut self.words[idx / WORD_BITS];
+        let mask = 1 << (idx % WORD_BITS);
+        let previous = *word & mask != 0;
+
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+
+        previous
+    }
+
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// A set of cells of a maze, using one bit per cell
+#[derive(Debug, Clone)]
+pub struct CellSet {
+    topology: Arc<dyn Topology>,
+    bits: BitSet,
+    /// The number of cells in the set, so that it doesn't have to be counted
+    len: usize,
+}
+
+impl CellSet {
+    pub fn new(maze: &Maze) -> CellSet {
+        CellSet {
+            topology: maze.topology().clone(),
+            bits: BitSet::new(maze.cell_count()),
+            len: 0,
+        }
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        self.topology.index(coord)
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        match self.index(coord) {
+            Some(idx) => self.bits.get(idx),
+            None => false,
+        }
+    }
+
+    /// Adds a cell to the set, returning whether it wasn't already present
+    pub fn insert(&mut self, coord: Coord) -> bool {
+        let inserted = match self.index(&coord) {
+            Some(idx) => !self.bits.set(idx, true),
+            None => false,
+        };
+        if inserted {
+            self.len += 1;
+        }
+
+        inserted
+    }
+
+    /// Removes a cell from the set, returning whether it was present
+    pub fn remove(&mut self, coord: &Coord) -> bool {
+        let removed = match self.index(coord) {
+            Some(idx) => self.bits.set(idx, false),
+            None => false,
+        };
+        if removed {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_sets_count_their_cells() {
+        let maze = Maze::new(4, 3);
+        let mut set = CellSet::new(&maze);
+        assert!(set.is_empty());
+
+        assert!(set.insert([1, 2].into()));
+        assert!(!set.insert([1, 2].into()));
+        assert!(!set.insert([4, 0].into()));
+        assert!(set.insert([0, 0].into()));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove(&[1, 2].into()));
+        assert!(!set.remove(&[1, 2].into()));
+        assert_eq!(set.len(), 1);
+
+        set.clear();
+        assert!(set.is_empty() && !set.contains(&[0, 0].into()));
+    }
+}
@@ -0,0 +1,22 @@
+use std::time::{Duration, Instant};
+
+/// How long a generator or solver is allowed to run for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// Run until done
+    Unlimited,
+    /// Run for at most this many ticks
+    Ticks(usize),
+    /// Run until this much time has passed, always doing at least one tick
+    Time(Duration),
+}
+
+impl Budget {
+    pub fn exhausted(&self, ticks: usize, started: Instant) -> bool {
+        match *self {
+            Budget::Unlimited => false,
+            Budget::Ticks(max) => ticks >= max,
+            Budget::Time(max) => ticks > 0 && started.elapsed() >= max,
+        }
+    }
+}
@@ -1,32 +1,87 @@
-use generator::GeneratorType;
-use maze::Coord;
-use solver::SolverType;
+// The window options are still parsed without the visualizer, but nothing reads them
+#![cfg_attr(not(feature = "visualizer"), allow(dead_code))]
 
-pub const COLOR_BACKGROUND: (u8, u8, u8) = (7, 16, 19);
-pub const COLOR_START: (u8, u8, u8) = (149, 198, 35);
-pub const COLOR_END: (u8, u8, u8) = (229, 88, 18);
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-pub const COLOR_WALL: (u8, u8, u8) = (239, 231, 218);
+use maze::{
+    Coord, Error, Mask, Masked, MazeBuilder, Params, Random, Registry, Seed, Stream, Topology,
+    TopologyType, Wrap,
+};
 
-pub const COLOR_EXPLORED: (u8, u8, u8) = (14, 71, 73);
-pub const COLOR_HIGHLIGHT_BRIGHT: (u8, u8, u8) = (163, 187, 173);
-pub const COLOR_HIGHLIGHT_MEDIUM: (u8, u8, u8) = (53, 114, 102);
-pub const COLOR_HIGHLIGHT_DARK: (u8, u8, u8) = (57, 104, 106);
+fn generator_names() -> Vec<&'static str> {
+    Registry::default().generator_names()
+}
+
+fn solver_names() -> Vec<&'static str> {
+    Registry::default().solver_names()
+}
+
+fn topology_names() -> Vec<&'static str> {
+    TopologyType::all().iter().map(|t| t.name()).collect()
+}
+
+fn wrap_names() -> Vec<&'static str> {
+    Wrap::all().iter().map(|w| w.name()).collect()
+}
+
+// clap borrows help texts for as long as the app lives, so they are built once
+lazy_static! {
+    static ref GENERATORS_HELP: String = format!(
+        "The algorithm to use when generating the maze\n\n{}\n",
+        Registry::default().generators_help()
+    );
+    static ref SOLVERS_HELP: String = format!(
+        "The algorithm to use when solving the maze\n\n{}\n",
+        Registry::default().solvers_help()
+    );
+}
 
-pub const CELL_WALL_WIDTH: f32 = 1.0;
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    let mut parts = s.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(value)) if !name.is_empty() => {
+            Ok((name.to_string(), value.to_string()))
+        }
+        _ => Err(format!("Expected name=value, got {}", s)),
+    }
+}
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "populate", about = "Generate packages")]
 pub struct Config {
     /// The algorithm to use when generating the maze
     #[structopt(short = "g", long = "generator", default_value = "dfs",
-                raw(possible_values = "&GeneratorType::variants()"))]
-    generator: GeneratorType,
+                raw(possible_values = "&generator_names()",
+                    long_help = "&GENERATORS_HELP"))]
+    generator: String,
+
+    /// A parameter of the generator, as name=value
+    #[structopt(long = "gen-param", parse(try_from_str = "parse_param"),
+                raw(number_of_values = "1"))]
+    generator_params: Vec<(String, String)>,
 
     /// The algorithm to use when solving the maze
     #[structopt(short = "s", long = "solver", default_value = "astar",
-                raw(possible_values = "&SolverType::variants()"))]
-    solver: SolverType,
+                raw(possible_values = "&solver_names()",
+                    long_help = "&SOLVERS_HELP"))]
+    solver: String,
+
+    /// A parameter of the solver, as name=value
+    #[structopt(long = "solve-param", parse(try_from_str = "parse_param"),
+                raw(number_of_values = "1"))]
+    solver_params: Vec<(String, String)>,
+
+    /// The shape of the cells of the maze
+    #[structopt(short = "t", long = "topology", default_value = "grid",
+                raw(possible_values = "&topology_names()"))]
+    topology: TopologyType,
+
+    /// Joins the east and west borders of a grid (cylinder), or also the north and south ones
+    /// (torus)
+    #[structopt(long = "wrap", default_value = "none",
+                raw(possible_values = "&wrap_names()"))]
+    wrap: Wrap,
 
     /// Updates per second
     #[structopt(long = "ups", default_value = "60")]
@@ -44,6 +99,15 @@ pub struct Config {
     #[structopt(short = "h", long = "height", default_value = "18")]
     height: u32,
 
+    /// The number of floors of the maze, connected by stairs. Only grids can have more than one
+    #[structopt(long = "depth", default_value = "1")]
+    depth: u32,
+
+    /// Only keep the cells of this mask, read as a PBM image if it ends in .pbm or as text
+    /// otherwise. The size of the maze is the size of the mask.
+    #[structopt(long = "mask", parse(from_os_str))]
+    mask: Option<PathBuf>,
+
     /// The starting point of the maze
     #[structopt(long = "start")]
     start: Option<Coord>,
@@ -64,20 +128,55 @@ pub struct Config {
     #[structopt(long = "no-print-fps")]
     print_fps: bool,
 
-    /// If provided, the maze will be generated using a static seed
+    /// If provided, the maze will be generated using this seed, either a number or any text
     #[structopt(long = "seed")]
-    seed: Option<u32>,
+    seed: Option<Seed>,
+
+    /// If provided, the maze is generated and solved without opening a window
+    #[structopt(long = "headless")]
+    headless: bool,
+
+    /// In headless mode, write the solved maze to this file, as an image if it ends in .svg or as
+    /// text otherwise
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
 }
 
 impl Config {
     #[inline]
-    pub fn generator(&self) -> GeneratorType {
-        self.generator
+    pub fn generator(&self) -> &str {
+        &self.generator
+    }
+
+    pub fn generator_params(&self) -> Params {
+        let mut params = Params::new();
+        for (name, value) in &self.generator_params {
+            params.set(name.as_str(), value.as_str());
+        }
+        params
     }
 
     #[inline]
-    pub fn solver(&self) -> SolverType {
-        self.solver
+    pub fn solver(&self) -> &str {
+        &self.solver
+    }
+
+    pub fn solver_params(&self) -> Params {
+        let mut params = Params::new();
+        for (name, value) in &self.solver_params {
+            params.set(name.as_str(), value.as_str());
+        }
+        params
+    }
+
+    #[inline]
+    pub fn topology(&self) -> TopologyType {
+        self.topology
+    }
+
+    #[inline]
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
     }
 
     #[inline]
@@ -106,13 +205,8 @@ impl Config {
     }
 
     #[inline]
-    pub fn window_width(&self) -> u32 {
-        self.cell_width() * self.maze_width()
-    }
-
-    #[inline]
-    pub fn window_height(&self) -> u32 {
-        self.cell_height() * self.maze_height()
+    pub fn maze_depth(&self) -> u32 {
+        self.depth
     }
 
     #[inline]
@@ -141,16 +235,51 @@ impl Config {
     }
 
     #[inline]
-    pub fn seed(&self) -> Option<[usize; 4]> {
-        match self.seed {
-            None => None,
-            Some(seed) => {
-                let b1 = ((seed >> 24) & 0xff) as usize;
-                let b2 = ((seed >> 16) & 0xff) as usize;
-                let b3 = ((seed >> 8) & 0xff) as usize;
-                let b4 = (seed & 0xff) as usize;
-                Some([b1, b2, b3, b4])
+    pub fn seed(&self) -> Option<Seed> {
+        self.seed
+    }
+
+    #[inline]
+    pub fn headless(&self) -> bool {
+        self.headless
+    }
+
+    #[inline]
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    #[inline]
+    pub fn mask(&self) -> Option<&Path> {
+        self.mask.as_deref()
+    }
+
+    /// The builder of the maze described by the arguments. Irregular shapes are laid out from
+    /// `seed`.
+    pub fn maze_builder(&self, seed: Seed) -> maze::Result<MazeBuilder> {
+        let topology: Arc<dyn Topology> = match self.mask() {
+            Some(path) => {
+                let grid = self.topology() == TopologyType::Grid;
+                if !grid || self.wrap() != Wrap::None || self.maze_depth() != 1 {
+                    return Err(Error::UnsupportedShape("Masks".to_string()));
+                }
+                Arc::new(Masked::new(&Mask::load(path)?)?)
             }
+            None => self.topology().init(
+                self.maze_width(),
+                self.maze_height(),
+                self.maze_depth(),
+                self.wrap(),
+                &mut Random::new(seed, Stream::Layout),
+            )?,
+        };
+        let mut builder = MazeBuilder::with_topology(topology);
+        if let Some(start) = self.start() {
+            builder = builder.start(start);
+        }
+        if let Some(end) = self.end() {
+            builder = builder.end(end);
         }
+        Ok(builder)
     }
 }
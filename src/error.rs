@@ -9,10 +9,21 @@ pub type Result<T> = StdResult<T, Error>;
 pub enum Error {
     NotNeighbours(Coord, Coord),
     BorderWall(Wall),
+    CrossedWall(Wall),
     MissingSet(Coord),
     UnsupportedGenerator(String),
     UnsupportedSolver(String),
+    UnsupportedTopology(String),
+    UnsupportedWrap(String),
+    UnsupportedShape(String),
     ImpossibleMaze,
+    TooFewCells(usize),
+    OutOfBounds(Coord),
+    StartIsEnd(Coord),
+    UnknownParam(String),
+    MissingParam(String),
+    InvalidParam(String, String),
+    InvalidMask(String),
 }
 
 impl fmt::Display for Error {
@@ -22,10 +33,29 @@ impl fmt::Display for Error {
                 write!(f, "{} and {} are not neighbours", c1, c2)
             }
             Error::BorderWall(ref wall) => write!(f, "Tried to remove non border wall {}", wall),
+            Error::CrossedWall(ref wall) => {
+                write!(f, "Tried to remove wall {} crossing another passage", wall)
+            }
             Error::MissingSet(ref coord) => write!(f, "Missing set for coord {}", coord),
             Error::UnsupportedGenerator(ref name) => write!(f, "Unsupported generator {}", name),
             Error::UnsupportedSolver(ref name) => write!(f, "Unsupported solver {}", name),
+            Error::UnsupportedTopology(ref name) => write!(f, "Unsupported topology {}", name),
+            Error::UnsupportedWrap(ref name) => write!(f, "Unsupported wrap {}", name),
+            Error::UnsupportedShape(ref name) => {
+                write!(f, "{} doesn't support the shape of this maze", name)
+            }
             Error::ImpossibleMaze => write!(f, "Impossible maze"),
+            Error::TooFewCells(count) => {
+                write!(f, "A maze needs at least two cells, not {}", count)
+            }
+            Error::OutOfBounds(ref coord) => write!(f, "{} is outside of the maze", coord),
+            Error::StartIsEnd(ref coord) => write!(f, "Start and end are both {}", coord),
+            Error::UnknownParam(ref name) => write!(f, "Unknown parameter {}", name),
+            Error::MissingParam(ref name) => write!(f, "Missing parameter {}", name),
+            Error::InvalidParam(ref name, ref value) => {
+                write!(f, "Invalid value {} for parameter {}", value, name)
+            }
+            Error::InvalidMask(ref reason) => write!(f, "Invalid mask: {}", reason),
         }
     }
 }
@@ -35,10 +65,21 @@ impl error::Error for Error {
         match *self {
             Error::NotNeighbours(_, _) => "Two provided coordinates are not neighbours",
             Error::BorderWall(_) => "Tried to remove border wall",
+            Error::CrossedWall(_) => "Tried to remove a wall crossing another passage",
             Error::MissingSet(_) => "Missing set for a given coord",
             Error::UnsupportedGenerator(_) => "Unsupported generator",
             Error::UnsupportedSolver(_) => "Unsupported solver",
+            Error::UnsupportedTopology(_) => "Unsupported topology",
+            Error::UnsupportedWrap(_) => "Unsupported wrap",
+            Error::UnsupportedShape(_) => "Algorithm doesn't support the shape of the maze",
             Error::ImpossibleMaze => "Impossible maze",
+            Error::TooFewCells(_) => "Not enough cells for a maze",
+            Error::OutOfBounds(_) => "Coordinate outside of the maze",
+            Error::StartIsEnd(_) => "Start and end are the same cell",
+            Error::UnknownParam(_) => "Unknown parameter",
+            Error::MissingParam(_) => "Missing parameter",
+            Error::InvalidParam(_, _) => "Invalid parameter value",
+            Error::InvalidMask(_) => "Invalid mask",
         }
     }
 }
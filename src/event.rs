@@ -0,0 +1,71 @@
+use maze::Coord;
+
+/// Something that happened while a generator or a solver was running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The cells the algorithm is looking at during the current tick
+    Current(Vec<Coord>),
+    /// A cell was visited for the first time
+    CellVisited(Coord),
+    /// The wall between two cells was removed
+    PassageCarved(Coord, Coord),
+    /// The wall between two cells was put back
+    WallAdded(Coord, Coord),
+    /// A cell is waiting to be processed
+    FrontierAdded(Coord),
+    /// A cell is no longer waiting to be processed
+    FrontierRemoved(Coord),
+    /// A cell was added to the end of the path being followed
+    PathPushed(Coord),
+    /// A cell was removed from the end of the path being followed
+    Backtracked(Coord),
+    /// The path being followed was replaced
+    PathUpdated(Vec<Coord>),
+    /// A group of cells, such as a row, is being scanned
+    Scanning(Vec<Coord>),
+}
+
+impl Event {
+    /// The same event with every cell replaced by `f(cell)`
+    pub fn map_coords<F: Fn(Coord) -> Coord>(self, f: F) -> Event {
+        let all = |coords: Vec<Coord>| coords.into_iter().map(&f).collect();
+        match self {
+            Event::Current(coords) => Event::Current(all(coords)),
+            Event::CellVisited(c) => Event::CellVisited(f(c)),
+            Event::PassageCarved(c1, c2) => Event::PassageCarved(f(c1), f(c2)),
+            Event::WallAdded(c1, c2) => Event::WallAdded(f(c1), f(c2)),
+            Event::FrontierAdded(c) => Event::FrontierAdded(f(c)),
+            Event::FrontierRemoved(c) => Event::FrontierRemoved(f(c)),
+            Event::PathPushed(c) => Event::PathPushed(f(c)),
+            Event::Backtracked(c) => Event::Backtracked(f(c)),
+            Event::PathUpdated(coords) => Event::PathUpdated(all(coords)),
+            Event::Scanning(coords) => Event::Scanning(all(coords)),
+        }
+    }
+}
+
+/// Receives the events emitted by generators and solvers
+pub trait Observer {
+    fn notify(&mut self, event: Event);
+
+    /// Whether the events are used at all. Events that are costly to build, like whole paths,
+    /// are skipped when they aren't.
+    fn is_listening(&self) -> bool {
+        true
+    }
+}
+
+/// Ignores every event
+impl Observer for () {
+    fn notify(&mut self, _event: Event) {}
+
+    fn is_listening(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(Event)> Observer for F {
+    fn notify(&mut self, event: Event) {
+        self(event)
+    }
+}
@@ -1,10 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
 
-use rand::{Rng, StdRng};
-
+use bitset::CellSet;
+use budget::Budget;
 use error::{Error, Result};
+use event::{Event, Observer};
 use maze::{Coord, Direction, Maze, Wall};
+use random::Random;
+use registry::{Param, Params};
+use topology::Topology;
+
+/// The probability of closing a run of the sidewinder algorithm when it isn't given
+const SIDEWINDER_CLOSE: f32 = 0.5;
+/// The strategy of the growing tree algorithm when it isn't given
+const GROWING_TREE_STRATEGY: Pick = Pick::Newest;
 
 #[derive(Debug, Clone, Copy)]
 pub enum GeneratorType {
@@ -13,74 +25,275 @@ pub enum GeneratorType {
     Prim,
     Eller,
     HuntKill,
+    Wilson,
+    AldousBroder,
+    RecursiveDivision,
+    BinaryTree,
+    Sidewinder,
+    GrowingTree,
 }
 
 impl GeneratorType {
-    /// A list of possible variants in `&'static str` form
-    pub fn variants() -> [&'static str; 5] {
-        ["dfs", "kruskal", "prim", "eller", "hunt-kill"]
+    pub fn all() -> [GeneratorType; 11] {
+        [
+            GeneratorType::DFS,
+            GeneratorType::Kruskal,
+            GeneratorType::Prim,
+            GeneratorType::Eller,
+            GeneratorType::HuntKill,
+            GeneratorType::Wilson,
+            GeneratorType::AldousBroder,
+            GeneratorType::RecursiveDivision,
+            GeneratorType::BinaryTree,
+            GeneratorType::Sidewinder,
+            GeneratorType::GrowingTree,
+        ]
     }
 
-    pub fn init(&self, maze: &Maze, random: &mut StdRng) -> Box<dyn Generator> {
+    pub fn name(&self) -> &'static str {
         match *self {
+            GeneratorType::DFS => "dfs",
+            GeneratorType::Kruskal => "kruskal",
+            GeneratorType::Prim => "prim",
+            GeneratorType::Eller => "eller",
+            GeneratorType::HuntKill => "hunt-kill",
+            GeneratorType::Wilson => "wilson",
+            GeneratorType::AldousBroder => "aldous-broder",
+            GeneratorType::RecursiveDivision => "recursive-division",
+            GeneratorType::BinaryTree => "binary-tree",
+            GeneratorType::Sidewinder => "sidewinder",
+            GeneratorType::GrowingTree => "growing-tree",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match *self {
+            GeneratorType::DFS => "Randomized depth first search, long and winding corridors",
+            GeneratorType::Kruskal => "Randomized Kruskal, joins random cells into a spanning tree",
+            GeneratorType::Prim => "Randomized Prim, grows the maze outwards from the start",
+            GeneratorType::Eller => "Eller's algorithm, builds the maze one row at a time",
+            GeneratorType::HuntKill => "Hunt and kill, random walks restarted by scanning rows",
+            GeneratorType::Wilson => "Wilson's algorithm, loop-erased random walks, unbiased",
+            GeneratorType::AldousBroder => "Aldous-Broder, one random walk, unbiased but slow",
+            GeneratorType::RecursiveDivision => {
+                "Recursive division, splits an empty maze with walls, long straight corridors"
+            }
+            GeneratorType::BinaryTree => "Binary tree, a random passage towards a corner per cell",
+            GeneratorType::Sidewinder => "Sidewinder, runs along rows closed towards one side",
+            GeneratorType::GrowingTree => {
+                "Growing tree, grows from the newest, a random or the oldest cell, or a mix"
+            }
+        }
+    }
+
+    pub fn init(&self, maze: &Maze, random: &mut Random) -> Result<Box<dyn Generator>> {
+        Ok(match *self {
             GeneratorType::DFS => Box::new(DFS::new(maze)),
             GeneratorType::Kruskal => Box::new(Kruskal::new(maze, random)),
             GeneratorType::Prim => Box::new(Prim::new(maze)),
-            GeneratorType::Eller => Box::new(Eller::new(maze)),
+            GeneratorType::Eller => Box::new(Eller::new(maze)?),
             GeneratorType::HuntKill => Box::new(HuntKill::new(maze)),
+            GeneratorType::Wilson => Box::new(Wilson::new(maze, random)),
+            GeneratorType::AldousBroder => Box::new(AldousBroder::new(maze)),
+            GeneratorType::RecursiveDivision => Box::new(RecursiveDivision::new(maze)?),
+            GeneratorType::BinaryTree => Box::new(BinaryTree::new(maze, Bias::NorthEast)?),
+            GeneratorType::Sidewinder => {
+                Box::new(Sidewinder::new(maze, Bias::NorthEast, SIDEWINDER_CLOSE)?)
+            }
+            GeneratorType::GrowingTree => Box::new(GrowingTree::new(
+                maze,
+                Strategy::single(GROWING_TREE_STRATEGY),
+            )),
+        })
+    }
+
+    /// The error given when this generator can't build mazes of some shape
+    pub fn unsupported(&self) -> Error {
+        Error::UnsupportedShape(self.name().to_string())
+    }
+
+    /// The parameters `init_with` accepts
+    pub fn params(&self) -> Vec<Param> {
+        let bias = Param {
+            name: "bias",
+            description: "the corner passages lean towards: north-east, north-west, south-east \
+                          or south-west",
+            default: "north-east",
+        };
+
+        match *self {
+            GeneratorType::BinaryTree => vec![bias],
+            GeneratorType::Sidewinder => vec![
+                bias,
+                Param {
+                    name: "close",
+                    description: "the probability of closing a run at every cell",
+                    default: "0.5",
+                },
+            ],
+            GeneratorType::GrowingTree => vec![Param {
+                name: "strategy",
+                description: "the cell to grow from: newest, random, oldest or a mix such as \
+                              \"75% newest / 25% random\"",
+                default: "newest",
+            }],
+            _ => vec![],
         }
     }
+
+    /// Same as `init`, with a value for every parameter of `params`
+    pub fn init_with(
+        &self,
+        maze: &Maze,
+        random: &mut Random,
+        params: &Params,
+    ) -> Result<Box<dyn Generator>> {
+        Ok(match *self {
+            GeneratorType::BinaryTree => Box::new(BinaryTree::new(maze, params.get("bias")?)?),
+            GeneratorType::Sidewinder => Box::new(Sidewinder::new(
+                maze,
+                params.get("bias")?,
+                params.get("close")?,
+            )?),
+            GeneratorType::GrowingTree => Box::new(GrowingTree::new(maze, params.get("strategy")?)),
+            _ => self.init(maze, random)?,
+        })
+    }
 }
 
 impl FromStr for GeneratorType {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_ref() {
-            "dfs" => Ok(GeneratorType::DFS),
-            "kruskal" => Ok(GeneratorType::Kruskal),
-            "prim" => Ok(GeneratorType::Prim),
-            "eller" => Ok(GeneratorType::Eller),
-            "hunt-kill" => Ok(GeneratorType::HuntKill),
-            _ => Err(Error::UnsupportedGenerator(s.to_string())),
-        }
+        let lowercase = s.to_lowercase();
+        GeneratorType::all()
+            .iter()
+            .find(|g| g.name() == lowercase)
+            .cloned()
+            .ok_or_else(|| Error::UnsupportedGenerator(s.to_string()))
     }
 }
 
 pub trait Generator {
     fn is_done(&self) -> bool;
-    fn tick(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()>;
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()>;
+
+    /// Ticks until the maze is generated or the budget runs out, returning whether it's done
+    fn run_for(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+        budget: Budget,
+    ) -> Result<bool> {
+        let started = Instant::now();
+        let mut ticks = 0;
+
+        while !self.is_done() && !budget.exhausted(ticks, started) {
+            self.tick(maze, random, observer)?;
+            ticks += 1;
+        }
+
+        Ok(self.is_done())
+    }
+}
+
+/// Whether the maze is a full rectangle of square cells
+fn is_rectangle(maze: &Maze) -> bool {
+    let (width, height) = maze.topology().size();
+    maze.has_square_cells() && (width * height) as usize == maze.cell_count()
+}
+
+/// The neighbour in `direction`, unless the maze wraps around to get there. Row based
+/// generators walk rows from one side to the other, so passages that wrap around are never
+/// carved.
+fn straight_neighbour(maze: &Maze, coord: &Coord, direction: Direction) -> Option<Coord> {
+    maze.neighbour(coord, &direction)
+        .filter(|neighbour| *neighbour == coord.step(direction))
+}
+
+/// The cells in the same row and floor as `coord`, the indices around it
+fn row_of(maze: &Maze, coord: &Coord) -> Vec<Coord> {
+    let topology = maze.topology();
+    let idx = match topology.index(coord) {
+        Some(idx) => idx,
+        None => return vec![],
+    };
+    let same_row = |idx: usize| {
+        let cell = topology.coord(idx);
+        cell.y == coord.y && cell.z == coord.z
+    };
+
+    let (mut first, mut last) = (idx, idx);
+    while first > 0 && same_row(first - 1) {
+        first -= 1;
+    }
+    while last + 1 < topology.cell_count() && same_row(last + 1) {
+        last += 1;
+    }
+
+    (first..=last).map(|idx| topology.coord(idx)).collect()
+}
+
+/// Carves a fully walled maze using the given algorithm
+pub fn generate(maze: &mut Maze, generator: GeneratorType, random: &mut Random) -> Result<()> {
+    generate_with(maze, generator, random, &mut ())
+}
+
+/// Same as `generate`, reporting every step to `observer`
+pub fn generate_with(
+    maze: &mut Maze,
+    generator: GeneratorType,
+    random: &mut Random,
+    observer: &mut dyn Observer,
+) -> Result<()> {
+    let mut generator = generator.init(maze, random)?;
+    generator.run_for(maze, random, observer, Budget::Unlimited)?;
+
+    Ok(())
 }
 
 pub struct DFS {
     pub current: Option<Coord>,
     pub stack: Vec<Coord>,
+    visited: CellSet,
+    /// Whether the search went on from the end, to reach the cells that are only reachable
+    /// through it
+    past_end: bool,
 }
 
 impl DFS {
-    fn new(maze: &Maze) -> DFS {
+    pub fn new(maze: &Maze) -> DFS {
         DFS {
             current: Some(maze.start),
             stack: vec![],
+            visited: CellSet::new(maze),
+            past_end: false,
         }
     }
 
-    fn available_neighbour(&self, maze: &Maze, random: &mut StdRng) -> Option<(Coord, Direction)> {
+    fn available_neighbour(&self, maze: &Maze, random: &mut Random) -> Option<(Coord, Direction)> {
         let current = match self.current {
             Some(ref current) => current,
             None => return None,
         };
 
-        if maze.end == *current {
+        // The end is left as a dead end, unless there's no other way to the cells behind it
+        if maze.end == *current && !self.past_end {
             return None;
         }
 
-        let mut neighbours = maze.neighbours(current);
+        let mut neighbours = maze.linkable_neighbours(current);
         random.shuffle(&mut neighbours);
 
         neighbours
             .into_iter()
-            .find(|(c, _)| !maze.explored.contains(&c))
+            .find(|(c, _)| !self.visited.contains(c))
     }
 }
 
@@ -89,27 +302,38 @@ impl Generator for DFS {
         self.current.is_none()
     }
 
-    fn tick(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
-        maze.highlight_bright.clear();
-
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
         let current = match self.current {
             Some(ref current) => *current,
             None => return Ok(()),
         };
 
-        maze.highlight_bright.insert(current);
-        maze.highlight_medium.insert(current);
-        maze.explored.insert(current);
+        observer.notify(Event::Current(vec![current]));
+        if self.visited.insert(current) {
+            observer.notify(Event::CellVisited(current));
+            observer.notify(Event::PathPushed(current));
+        }
 
-        match self.available_neighbour(&maze, random) {
+        match self.available_neighbour(maze, random) {
             Some((neighbour, _)) => {
                 maze.link(&current, &neighbour)?;
+                observer.notify(Event::PassageCarved(current, neighbour));
                 self.stack.push(current);
                 self.current = Some(neighbour);
             }
             None => {
-                maze.highlight_medium.remove(&current);
+                observer.notify(Event::Backtracked(current));
                 self.current = self.stack.pop();
+
+                if self.current.is_none() && !self.past_end && self.visited.contains(&maze.end) {
+                    self.past_end = true;
+                    self.current = Some(maze.end);
+                }
             }
         }
 
@@ -124,63 +348,52 @@ enum JoinResult {
 
 pub struct Kruskal {
     walls: Vec<Wall>,
-    sets: Vec<HashSet<Coord>>,
+    topology: Arc<dyn Topology>,
+    /// A disjoint set forest of the cells, by index
+    parents: Vec<usize>,
+    sets: usize,
+    visited: CellSet,
 }
 
 impl Kruskal {
-    pub fn new(maze: &Maze, random: &mut StdRng) -> Kruskal {
-        let mut walls = maze
-            .walls
-            .iter()
-            .filter(|w| w.removable())
-            .cloned()
-            .collect::<Vec<_>>();
-
+    pub fn new(maze: &Maze, random: &mut Random) -> Kruskal {
+        let mut walls = maze.topology().edges();
         random.shuffle(&mut walls);
 
+        let cell_count = maze.cell_count();
         Kruskal {
             walls,
-            sets: maze
-                .cells
-                .keys()
-                .map(|c| {
-                    let mut set = HashSet::new();
-                    set.insert(*c);
-
-                    set
-                })
-                .collect(),
+            topology: maze.topology().clone(),
+            parents: (0..cell_count).collect(),
+            sets: cell_count,
+            visited: CellSet::new(maze),
         }
     }
 
-    fn join(&mut self, c1: Coord, c2: Coord, maze: &Maze) -> Result<JoinResult> {
-        let mut c1_set = self
-            .sets
-            .drain_filter(|s| s.contains(&c1))
-            .next()
-            .ok_or_else(|| Error::MissingSet(c1))?;
+    fn index(&self, c: &Coord) -> Result<usize> {
+        self.topology.index(c).ok_or(Error::MissingSet(*c))
+    }
 
-        if c1_set.contains(&c2) {
-            self.sets.push(c1_set);
-            return Ok(JoinResult::Nop);
+    fn find(&mut self, mut idx: usize) -> usize {
+        while self.parents[idx] != idx {
+            self.parents[idx] = self.parents[self.parents[idx]];
+            idx = self.parents[idx];
         }
 
-        let c2_set = self
-            .sets
-            .drain_filter(|s| s.contains(&c2))
-            .next()
-            .ok_or_else(|| Error::MissingSet(c2))?;
+        idx
+    }
 
-        for c in c2_set {
-            for (neighbour, direction) in maze.neighbours(&c) {
-                if c1_set.contains(&neighbour) {
-                    self.walls.remove_item(&maze.wall(&c, &direction));
-                }
-            }
-            c1_set.insert(c);
+    fn join(&mut self, c1: Coord, c2: Coord) -> Result<JoinResult> {
+        let (c1_idx, c2_idx) = (self.index(&c1)?, self.index(&c2)?);
+        let c1_set = self.find(c1_idx);
+        let c2_set = self.find(c2_idx);
+
+        if c1_set == c2_set {
+            return Ok(JoinResult::Nop);
         }
 
-        self.sets.push(c1_set);
+        self.parents[c2_set] = c1_set;
+        self.sets -= 1;
 
         Ok(JoinResult::Joined)
     }
@@ -188,29 +401,40 @@ impl Kruskal {
 
 impl Generator for Kruskal {
     fn is_done(&self) -> bool {
-        self.walls.is_empty() || self.sets.len() <= 1
+        self.walls.is_empty() || self.sets <= 1
     }
 
-    fn tick(&mut self, maze: &mut Maze, _random: &mut StdRng) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        _random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
         if self.is_done() {
             return Ok(());
         }
 
         if let Some(wall) = self.walls.pop() {
-            let (c1, c2) = maze.divided_coords(&wall);
+            let (c1, c2) = wall.divided_coords();
 
-            maze.explored.insert(c1);
-            maze.explored.insert(c2);
-            maze.highlight_bright.insert(c1);
-            maze.highlight_bright.insert(c2);
+            observer.notify(Event::Current(vec![c1, c2]));
+            for c in &[c1, c2] {
+                if self.visited.insert(*c) {
+                    observer.notify(Event::CellVisited(*c));
+                }
+            }
+
+            // Weave mazes only allow some walls to be removed, depending on the ones already gone
+            if !maze.can_remove_wall(&wall) {
+                return Ok(());
+            }
 
-            match self.join(c1, c2, maze) {
-                Err(e) => return Err(e),
-                Ok(JoinResult::Joined) => {
-                    maze.walls.remove(&wall);
+            match self.join(c1, c2)? {
+                JoinResult::Joined => {
+                    maze.remove_wall(&wall);
+                    observer.notify(Event::PassageCarved(c1, c2));
                 }
-                Ok(JoinResult::Nop) => {}
+                JoinResult::Nop => {}
             };
         }
 
@@ -219,72 +443,86 @@ impl Generator for Kruskal {
 }
 
 pub struct Prim {
-    cells: HashSet<Coord>,
+    frontier: Vec<Coord>,
+    in_frontier: CellSet,
+    visited: CellSet,
 }
 
 impl Prim {
     pub fn new(maze: &Maze) -> Prim {
-        let mut cells = HashSet::new();
-        cells.insert(maze.start);
+        let mut in_frontier = CellSet::new(maze);
+        in_frontier.insert(maze.start);
 
-        Prim { cells }
+        Prim {
+            frontier: vec![maze.start],
+            in_frontier,
+            visited: CellSet::new(maze),
+        }
     }
 
-    fn random_cell(&mut self, random: &mut StdRng) -> Option<Coord> {
-        if self.cells.is_empty() {
+    fn random_cell(&mut self, random: &mut Random) -> Option<usize> {
+        if self.frontier.is_empty() {
             return None;
         }
-        let cell_list: Vec<_> = self.cells.iter().collect();
-        // Unwrap is safe here because of the is_empty check above
-        let cell = random.choose(&cell_list).unwrap();
 
-        Some(**cell)
+        Some(random.index(self.frontier.len()))
     }
 }
 
 impl Generator for Prim {
     fn is_done(&self) -> bool {
-        self.cells.is_empty()
+        self.frontier.is_empty()
     }
 
-    fn tick(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
-        maze.highlight_bright.clear();
-
-        if let Some(cell) = self.random_cell(random) {
-            if (cell == maze.start || cell == maze.end) && maze.explored.contains(&cell) {
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if let Some(idx) = self.random_cell(random) {
+            let cell = self.frontier[idx];
+            if (cell == maze.start || cell == maze.end) && self.visited.contains(&cell) {
                 return Ok(());
             }
 
-            maze.explored.insert(cell);
-            maze.highlight_medium.remove(&cell);
-            maze.highlight_bright.insert(cell);
-            self.cells.remove(&cell);
+            self.frontier.swap_remove(idx);
+            self.in_frontier.remove(&cell);
+            observer.notify(Event::Current(vec![cell]));
+            observer.notify(Event::FrontierRemoved(cell));
 
             let explored_neighbours: Vec<_> = maze
-                .neighbours(&cell)
+                .linkable_neighbours(&cell)
                 .into_iter()
-                .filter(|(n, _)| maze.explored.contains(n))
+                .filter(|(n, _)| self.visited.contains(n))
                 .collect();
 
+            // The tunnel that put the cell in the frontier might be blocked by now, in weave
+            // mazes. It's added back when one of its other neighbours is visited.
+            if explored_neighbours.is_empty() && cell != maze.start {
+                return Ok(());
+            }
+
+            self.visited.insert(cell);
+            observer.notify(Event::CellVisited(cell));
+
             let unknown_neighbours: Vec<_> = maze
                 .neighbours(&cell)
                 .into_iter()
-                .filter(|(n, _)| !maze.explored.contains(n))
+                .filter(|(n, _)| !self.visited.contains(n))
                 .collect();
 
-            if let Some((_, direction)) = random.choose(&explored_neighbours) {
-                let wall = match direction {
-                    Direction::North => maze.north_wall(&cell),
-                    Direction::East => maze.east_wall(&cell),
-                    Direction::South => maze.south_wall(&cell),
-                    Direction::West => maze.west_wall(&cell),
-                };
-                maze.walls.remove(&wall);
+            if let Some((neighbour, direction)) = random.choose(&explored_neighbours) {
+                let wall = maze.wall(&cell, direction);
+                maze.remove_wall(&wall);
+                observer.notify(Event::PassageCarved(cell, *neighbour));
             }
 
             for (unknown_neighbour, _) in unknown_neighbours {
-                maze.highlight_medium.insert(unknown_neighbour);
-                self.cells.insert(unknown_neighbour);
+                if self.in_frontier.insert(unknown_neighbour) {
+                    self.frontier.push(unknown_neighbour);
+                    observer.notify(Event::FrontierAdded(unknown_neighbour));
+                }
             }
         }
 
@@ -305,25 +543,38 @@ pub struct Eller {
     coord_to_set: HashMap<Coord, usize>,
     set_to_coords: HashMap<usize, Vec<Coord>>,
     last_set: usize,
+    /// The sets with a passage to the row below `current`
+    connected: HashSet<usize>,
+    /// The cells in the same row as `current`
+    row: Vec<Coord>,
+    visited: CellSet,
 }
 
 impl Eller {
-    pub fn new(maze: &Maze) -> Eller {
-        let current = [0, 0].into();
+    /// Eller's algorithm works on full rows of square cells, so no other shapes are supported
+    pub fn new(maze: &Maze) -> Result<Eller> {
+        if !is_rectangle(maze) {
+            return Err(GeneratorType::Eller.unsupported());
+        }
+
+        let current = maze.topology().coord(0);
         let mut coord_to_set = HashMap::new();
         let mut set_to_coords = HashMap::new();
 
         set_to_coords.insert(0, vec![current]);
         coord_to_set.insert(current, 0);
 
-        Eller {
+        Ok(Eller {
             current,
-            last_row: maze.maze_height() as i32 - 1,
+            last_row: maze.topology().coord(maze.cell_count() - 1).y,
             mode: EllerMode::Horizontal,
             coord_to_set,
             set_to_coords,
             last_set: 0,
-        }
+            connected: HashSet::new(),
+            row: vec![],
+            visited: CellSet::new(maze),
+        })
     }
 
     fn same_set(&mut self, c1: &Coord, c2: &Coord) -> bool {
@@ -355,7 +606,7 @@ impl Eller {
             self.set_to_coords
                 .get_mut(&current_set)
                 .unwrap()
-                .remove_item(&c);
+                .retain(|cell| *cell != c);
             self.add(c, set);
             if self.set_to_coords[&current_set].is_empty() {
                 self.set_to_coords.remove(&current_set);
@@ -379,23 +630,32 @@ impl Eller {
         if !self.coord_to_set.contains_key(&c2) {
             self.add(c2, c1_set_idx);
         } else {
+            // The smaller set is moved into the bigger one, all at once
             let c2_set_idx = self.coord_to_set[&c2];
-            let c2_set: Vec<Coord> = self.set_to_coords[&c2_set_idx].to_vec();
+            let c1_len = self.set_to_coords[&c1_set_idx].len();
+            let c2_len = self.set_to_coords[&c2_set_idx].len();
+            let (from, to) = if c1_len < c2_len {
+                (c1_set_idx, c2_set_idx)
+            } else {
+                (c2_set_idx, c1_set_idx)
+            };
 
-            for &cell in &c2_set {
-                self.add(cell, c1_set_idx);
+            let moved = self.set_to_coords.remove(&from).unwrap_or_default();
+            for cell in &moved {
+                self.coord_to_set.insert(*cell, to);
             }
+            self.set_to_coords.entry(to).or_default().extend(moved);
         }
     }
 
-    fn connected_vertically(&self, set: usize, maze: &Maze) -> bool {
-        self.set_to_coords[&set]
-            .iter()
-            .filter(|c| c.y == self.current.y)
-            .any(|c| {
-                let wall = maze.south_wall(c);
-                !maze.walls.contains(&wall)
-            })
+    /// Drops the cells of the rows above `y`, which are never looked at again
+    fn forget_rows_above(&mut self, y: i32) {
+        self.coord_to_set.retain(|c, _| c.y >= y);
+        for coords in self.set_to_coords.values_mut() {
+            coords.retain(|c| c.y >= y);
+        }
+        self.set_to_coords.retain(|_, coords| !coords.is_empty());
+        self.connected.clear();
     }
 }
 
@@ -404,30 +664,34 @@ impl Generator for Eller {
         self.mode == EllerMode::Vertical && self.current.y == self.last_row
     }
 
-    fn tick(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
-        maze.highlight_bright.clear();
-        maze.highlight_medium.clear();
-        maze.highlight_dark.clear();
-
-        for x in 0..maze.maze_width() as i32 {
-            maze.highlight_dark.insert([x, self.current.y].into());
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if observer.is_listening() {
+            if self.row.first().map(|c| c.y) != Some(self.current.y) {
+                self.row = row_of(maze, &self.current);
+            }
+            observer.notify(Event::Scanning(self.row.clone()));
+        }
+        observer.notify(Event::Current(vec![self.current]));
+        if self.visited.insert(self.current) {
+            observer.notify(Event::CellVisited(self.current));
         }
-
-        maze.highlight_bright.insert(self.current);
-        maze.explored.insert(self.current);
 
         match self.mode {
             EllerMode::Horizontal => {
                 let current = self.current;
                 let last_row = current.y == self.last_row;
-                if let Some(neighbour) = maze.neighbour(&current, &Direction::East) {
-                    if !self.same_set(&current, &neighbour) && (last_row || random.gen()) {
+                if let Some(neighbour) = straight_neighbour(maze, &current, Direction::East) {
+                    if !self.same_set(&current, &neighbour) && (last_row || random.bool()) {
                         self.join(&current, neighbour);
 
                         let wall = maze.east_wall(&current);
-                        maze.walls.remove(&wall);
-
-                        maze.highlight_medium.insert(neighbour);
+                        maze.remove_wall(&wall);
+                        observer.notify(Event::PassageCarved(current, neighbour));
                     } else if !self.coord_to_set.contains_key(&neighbour) {
                         self.new_set(neighbour);
                     }
@@ -438,32 +702,36 @@ impl Generator for Eller {
             }
             EllerMode::Vertical => {
                 let current_set = self.coord_to_set[&self.current];
-                let last_in_set = maze
-                    .neighbour(&self.current, &Direction::West)
-                    .filter(|c| self.coord_to_set[&c] == current_set)
+                let last_in_set = straight_neighbour(maze, &self.current, Direction::West)
+                    .filter(|c| self.coord_to_set[c] == current_set)
                     .is_none();
-                let connected = self.connected_vertically(current_set, maze);
-                let force_join = last_in_set && !connected;
+                let force_join = last_in_set && !self.connected.contains(&current_set);
 
                 let current = self.current;
-                if force_join || random.gen() {
-                    if let Some(neighbour) = maze.neighbour(&current, &Direction::South) {
+                if force_join || random.bool() {
+                    if let Some(neighbour) = straight_neighbour(maze, &current, Direction::South) {
                         self.join(&current, neighbour);
+                        self.connected.insert(current_set);
 
                         let wall = maze.south_wall(&current);
-                        maze.walls.remove(&wall);
+                        maze.remove_wall(&wall);
+                        observer.notify(Event::PassageCarved(current, neighbour));
 
-                        maze.explored.insert(neighbour);
-                        maze.highlight_medium.insert(neighbour);
+                        if self.visited.insert(neighbour) {
+                            observer.notify(Event::CellVisited(neighbour));
+                        }
                     }
                 }
 
-                if let Some(neighbour) = maze.neighbour(&self.current, &Direction::West) {
+                if let Some(neighbour) = straight_neighbour(maze, &self.current, Direction::West) {
                     self.current = neighbour;
                 } else {
                     self.mode = EllerMode::Horizontal;
-                    if let Some(neighbour) = maze.neighbour(&self.current, &Direction::South) {
-                        if self.coord_to_set.get(&neighbour).is_none() {
+                    if let Some(neighbour) =
+                        straight_neighbour(maze, &self.current, Direction::South)
+                    {
+                        self.forget_rows_above(neighbour.y);
+                        if !self.coord_to_set.contains_key(&neighbour) {
                             self.new_set(neighbour);
                         }
                         self.current = neighbour;
@@ -484,125 +752,131 @@ enum HuntKillMode {
 
 pub struct HuntKill {
     current: Option<Coord>,
-    last_completed_column: i32,
-    last_completed_row: i32,
+    /// The index of the first cell that might not be visited yet
+    hunt_from: usize,
     mode: HuntKillMode,
+    visited: CellSet,
 }
 
 impl HuntKill {
-    pub fn new(_maze: &Maze) -> HuntKill {
+    pub fn new(maze: &Maze) -> HuntKill {
         HuntKill {
-            current: Some(Coord { x: 0, y: 0 }),
-            last_completed_column: 0,
-            last_completed_row: 0,
+            current: Some(maze.topology().coord(0)),
+            hunt_from: 0,
             mode: HuntKillMode::Kill,
+            visited: CellSet::new(maze),
         }
     }
 
-    fn available_neighbour(&self, maze: &Maze, random: &mut StdRng) -> Option<(Coord, Direction)> {
+    fn available_neighbour(&self, maze: &Maze, random: &mut Random) -> Option<(Coord, Direction)> {
         let current = match self.current {
             Some(ref current) => current,
             None => return None,
         };
 
-        let mut neighbours = maze.neighbours(current);
+        let mut neighbours = maze.linkable_neighbours(current);
         random.shuffle(&mut neighbours);
 
         neighbours
             .into_iter()
-            .find(|(c, _)| !maze.explored.contains(&c))
+            .find(|(c, _)| !self.visited.contains(c))
     }
 
-    fn current_row(&self, maze: &Maze) -> Vec<Coord> {
-        let current = match self.current {
-            Some(ref current) => current,
-            None => return vec![],
-        };
-
-        (0..maze.maze_width() as i32)
-            .into_iter()
-            .map(|x| [x, current.y].into())
-            .collect()
-    }
-
-    fn visited_neighbour(&self, maze: &Maze, random: &mut StdRng) -> Option<(Coord, Direction)> {
+    fn visited_neighbour(&self, maze: &Maze, random: &mut Random) -> Option<(Coord, Direction)> {
         let current = match self.current {
             Some(ref current) => current,
             None => return None,
         };
 
-        if maze.explored.contains(&current) {
+        if self.visited.contains(current) {
             return None;
         }
 
-        let mut neighbours = maze.neighbours(current);
+        let mut neighbours = maze.linkable_neighbours(current);
         random.shuffle(&mut neighbours);
 
         neighbours
             .into_iter()
-            .find(|(c, _)| maze.explored.contains(&c))
+            .find(|(c, _)| self.visited.contains(c))
+    }
+
+    /// Moves `hunt_from` past every visited cell, returning the cell it ends up on
+    fn first_unvisited(&mut self, maze: &Maze) -> Option<Coord> {
+        let topology = maze.topology();
+        while self.hunt_from < maze.cell_count() {
+            let coord = topology.coord(self.hunt_from);
+            if !self.visited.contains(&coord) {
+                return Some(coord);
+            }
+            self.hunt_from += 1;
+        }
+
+        None
     }
 
-    fn tick_kill(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
+    fn tick_kill(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
         let current = match self.current {
             Some(ref current) => *current,
             None => return Ok(()),
         };
 
-        maze.highlight_bright.insert(current);
-        maze.highlight_medium.insert(current);
-        maze.explored.insert(current);
+        observer.notify(Event::Current(vec![current]));
+        if self.visited.insert(current) {
+            observer.notify(Event::CellVisited(current));
+            observer.notify(Event::PathPushed(current));
+        }
 
-        match self.available_neighbour(&maze, random) {
+        match self.available_neighbour(maze, random) {
             Some((neighbour, _)) => {
                 maze.link(&current, &neighbour)?;
+                observer.notify(Event::PassageCarved(current, neighbour));
                 self.current = Some(neighbour);
             }
             None => {
                 self.mode = HuntKillMode::Hunt;
-                self.current = Some([self.last_completed_column, self.last_completed_row].into());
+                self.current = self.first_unvisited(maze);
             }
         };
         Ok(())
     }
 
-    fn tick_hunt(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
-        maze.highlight_medium.clear();
-
+    fn tick_hunt(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
         let current = match self.current {
             Some(ref current) => *current,
             None => return Ok(()),
         };
 
-        for c in self.current_row(maze) {
-            maze.highlight_medium.insert(c);
+        observer.notify(Event::PathUpdated(vec![]));
+        if observer.is_listening() {
+            observer.notify(Event::Scanning(row_of(maze, &current)));
         }
+        observer.notify(Event::Current(vec![current]));
 
-        maze.highlight_bright.insert(current);
-
-        match self.visited_neighbour(&maze, random) {
+        match self.visited_neighbour(maze, random) {
             Some((neighbour, _)) => {
-                maze.highlight_medium.clear();
-                maze.highlight_bright.insert(neighbour);
+                observer.notify(Event::Scanning(vec![]));
+                observer.notify(Event::Current(vec![current, neighbour]));
                 maze.link(&current, &neighbour)?;
-                self.last_completed_column = current.x;
+                observer.notify(Event::PassageCarved(current, neighbour));
                 self.mode = HuntKillMode::Kill;
             }
             None => {
-                if let Some(neighbour) = maze.neighbour(&current, &Direction::East) {
-                    self.current = Some(neighbour);
-                } else if current.y < maze.maze_height() as i32 - 1 {
-                    if self
-                        .current_row(maze)
-                        .iter()
-                        .all(|c| maze.explored.contains(c))
-                    {
-                        self.last_completed_row = current.y + 1;
-                    }
-                    self.current = Some([0, current.y + 1].into());
-                } else {
-                    self.current = None;
-                }
+                let topology = maze.topology().clone();
+                self.current = topology
+                    .index(&current)
+                    .map(|idx| idx + 1)
+                    .filter(|idx| *idx < maze.cell_count())
+                    .map(|idx| topology.coord(idx));
             }
         };
         Ok(())
@@ -614,12 +888,1027 @@ impl Generator for HuntKill {
         self.current.is_none()
     }
 
-    fn tick(&mut self, maze: &mut Maze, random: &mut StdRng) -> Result<()> {
-        maze.highlight_bright.clear();
-
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
         match self.mode {
-            HuntKillMode::Hunt => self.tick_hunt(maze, random),
-            HuntKillMode::Kill => self.tick_kill(maze, random),
+            HuntKillMode::Hunt => self.tick_hunt(maze, random, observer),
+            HuntKillMode::Kill => self.tick_kill(maze, random, observer),
         }
     }
 }
+
+/// Wilson's algorithm. Random walks wander from a cell outside of the maze until they reach it,
+/// erasing every loop they make on the way, and the walk left is carved into the maze. Every
+/// possible maze is equally likely.
+pub struct Wilson {
+    /// The cells left to start walks from, in random order. Some might be in the maze already.
+    pending: Vec<Coord>,
+    walk: Vec<Coord>,
+    /// The position of every cell of the walk
+    on_walk: HashMap<Coord, usize>,
+    in_maze: CellSet,
+}
+
+impl Wilson {
+    pub fn new(maze: &Maze, random: &mut Random) -> Wilson {
+        let mut pending: Vec<_> = maze.coords().filter(|c| *c != maze.start).collect();
+        random.shuffle(&mut pending);
+
+        Wilson {
+            pending,
+            walk: vec![],
+            on_walk: HashMap::new(),
+            in_maze: CellSet::new(maze),
+        }
+    }
+
+    /// Drops the pending cells that are in the maze already, from the end
+    fn skip_pending(&mut self) {
+        while let Some(coord) = self.pending.last() {
+            if !self.in_maze.contains(coord) {
+                break;
+            }
+            self.pending.pop();
+        }
+    }
+
+    fn push(&mut self, coord: Coord, observer: &mut dyn Observer) {
+        self.on_walk.insert(coord, self.walk.len());
+        self.walk.push(coord);
+        observer.notify(Event::PathPushed(coord));
+    }
+
+    /// Removes the loop that ends on `coord`, which is already part of the walk
+    fn erase_loop(&mut self, coord: &Coord, observer: &mut dyn Observer) {
+        let len = self.on_walk[coord] + 1;
+        for erased in self.walk.drain(len..) {
+            self.on_walk.remove(&erased);
+            observer.notify(Event::Backtracked(erased));
+        }
+    }
+
+    /// Carves the walk into the maze, starting from `reached`, the cell of the maze it ended on.
+    /// Walks that would cross a passage of a weave maze stop being carved there, and the cells
+    /// left out are walked from again later.
+    fn carve(&mut self, maze: &mut Maze, reached: Coord, observer: &mut dyn Observer) {
+        let mut previous = reached;
+        for i in (0..self.walk.len()).rev() {
+            let coord = self.walk[i];
+            let wall = maze
+                .neighbours(&coord)
+                .into_iter()
+                .find(|(n, _)| *n == previous)
+                .map(|(_, direction)| maze.wall(&coord, &direction));
+            match wall {
+                Some(wall) if maze.can_remove_wall(&wall) => {
+                    maze.remove_wall(&wall);
+                }
+                _ => {
+                    self.pending.push(self.walk[0]);
+                    break;
+                }
+            }
+
+            self.in_maze.insert(coord);
+            observer.notify(Event::CellVisited(coord));
+            observer.notify(Event::PassageCarved(coord, previous));
+            previous = coord;
+        }
+
+        self.walk.clear();
+        self.on_walk.clear();
+        observer.notify(Event::PathUpdated(vec![]));
+        self.skip_pending();
+    }
+}
+
+impl Generator for Wilson {
+    fn is_done(&self) -> bool {
+        !self.in_maze.is_empty() && self.walk.is_empty() && self.pending.is_empty()
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if self.in_maze.is_empty() {
+            self.in_maze.insert(maze.start);
+            observer.notify(Event::Current(vec![maze.start]));
+            observer.notify(Event::CellVisited(maze.start));
+            self.skip_pending();
+            return Ok(());
+        }
+
+        let current = match self.walk.last() {
+            Some(current) => *current,
+            None => match self.pending.pop() {
+                Some(start) => {
+                    observer.notify(Event::Current(vec![start]));
+                    self.push(start, observer);
+                    return Ok(());
+                }
+                None => return Ok(()),
+            },
+        };
+
+        let neighbours = maze.linkable_neighbours(&current);
+        let next = match random.choose(&neighbours) {
+            Some((next, _)) => *next,
+            None => return Ok(()),
+        };
+        observer.notify(Event::Current(vec![next]));
+
+        if self.in_maze.contains(&next) {
+            self.carve(maze, next, observer);
+        } else if self.on_walk.contains_key(&next) {
+            self.erase_loop(&next, observer);
+        } else {
+            self.push(next, observer);
+        }
+
+        Ok(())
+    }
+}
+
+/// The Aldous-Broder algorithm. A single random walk wanders around, over the cells it already
+/// visited too, carving a passage every time it steps into a new cell. Every possible maze is
+/// equally likely, but the last few cells can take a very long time to find.
+pub struct AldousBroder {
+    current: Coord,
+    visited: CellSet,
+    /// The number of cells that weren't visited yet
+    remaining: usize,
+}
+
+impl AldousBroder {
+    pub fn new(maze: &Maze) -> AldousBroder {
+        AldousBroder {
+            current: maze.start,
+            visited: CellSet::new(maze),
+            remaining: maze.cell_count(),
+        }
+    }
+}
+
+impl Generator for AldousBroder {
+    fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        let current = self.current;
+        if self.visited.insert(current) {
+            self.remaining -= 1;
+            observer.notify(Event::Current(vec![current]));
+            observer.notify(Event::CellVisited(current));
+            return Ok(());
+        }
+
+        let neighbours = maze.linkable_neighbours(&current);
+        let next = match random.choose(&neighbours) {
+            Some((next, _)) => *next,
+            None => return Ok(()),
+        };
+
+        if self.visited.insert(next) {
+            self.remaining -= 1;
+            maze.link(&current, &next)?;
+            observer.notify(Event::CellVisited(next));
+            observer.notify(Event::PassageCarved(current, next));
+        }
+        observer.notify(Event::Current(vec![next]));
+        self.current = next;
+
+        Ok(())
+    }
+}
+
+/// A rectangle of cells without any walls inside
+#[derive(Debug, Clone, Copy)]
+struct Chamber {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Chamber {
+    /// Chambers one cell wide are corridors, there's nothing left to split
+    fn can_split(&self) -> bool {
+        self.width > 1 && self.height > 1
+    }
+
+    fn coords(&self, z: i32) -> Vec<Coord> {
+        let mut coords = Vec::with_capacity((self.width * self.height) as usize);
+        for y in self.y..self.y + self.height {
+            for x in self.x..self.x + self.width {
+                coords.push([x, y, z].into());
+            }
+        }
+
+        coords
+    }
+}
+
+/// Recursive division. Starts by removing every wall, then splits the maze in two with a wall
+/// that has a single door, and keeps splitting the chambers on each side until they're only one
+/// cell wide.
+pub struct RecursiveDivision {
+    chambers: Vec<Chamber>,
+    /// Whether the walls were removed yet
+    emptied: bool,
+    z: i32,
+}
+
+impl RecursiveDivision {
+    /// Only rectangles of square cells can be split into rectangles, and walls that wrap around
+    /// the maze would never be put back
+    pub fn new(maze: &Maze) -> Result<RecursiveDivision> {
+        let wraps = maze.coords().any(|c| {
+            maze.neighbours(&c)
+                .into_iter()
+                .any(|(n, direction)| n != c.step(direction))
+        });
+        if !is_rectangle(maze) || wraps {
+            return Err(GeneratorType::RecursiveDivision.unsupported());
+        }
+
+        let (width, height) = maze.topology().size();
+        let origin = maze.topology().coord(0);
+        let chamber = Chamber {
+            x: origin.x,
+            y: origin.y,
+            width: width as i32,
+            height: height as i32,
+        };
+
+        Ok(RecursiveDivision {
+            chambers: if chamber.can_split() {
+                vec![chamber]
+            } else {
+                vec![]
+            },
+            emptied: false,
+            z: origin.z,
+        })
+    }
+
+    /// Walls up the south side of `row`, except at `door`
+    fn add_row_wall(
+        &self,
+        maze: &mut Maze,
+        chamber: &Chamber,
+        row: i32,
+        door: i32,
+        observer: &mut dyn Observer,
+    ) -> Vec<Coord> {
+        let mut cells = vec![];
+        for x in chamber.x..chamber.x + chamber.width {
+            let coord: Coord = [x, row, self.z].into();
+            cells.push(coord);
+            if x != door && maze.add_wall(&maze.south_wall(&coord)) {
+                observer.notify(Event::WallAdded(coord, coord.step(Direction::South)));
+            }
+        }
+
+        cells
+    }
+
+    /// Walls up the east side of `column`, except at `door`
+    fn add_column_wall(
+        &self,
+        maze: &mut Maze,
+        chamber: &Chamber,
+        column: i32,
+        door: i32,
+        observer: &mut dyn Observer,
+    ) -> Vec<Coord> {
+        let mut cells = vec![];
+        for y in chamber.y..chamber.y + chamber.height {
+            let coord: Coord = [column, y, self.z].into();
+            cells.push(coord);
+            if y != door && maze.add_wall(&maze.east_wall(&coord)) {
+                observer.notify(Event::WallAdded(coord, coord.step(Direction::East)));
+            }
+        }
+
+        cells
+    }
+}
+
+impl Generator for RecursiveDivision {
+    fn is_done(&self) -> bool {
+        self.emptied && self.chambers.is_empty()
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if !self.emptied {
+            for wall in maze.topology().edges() {
+                if maze.remove_wall(&wall) {
+                    let (c1, c2) = wall.divided_coords();
+                    observer.notify(Event::PassageCarved(c1, c2));
+                }
+            }
+            self.emptied = true;
+            return Ok(());
+        }
+
+        let chamber = match self.chambers.pop() {
+            Some(chamber) => chamber,
+            None => return Ok(()),
+        };
+        observer.notify(Event::Scanning(chamber.coords(self.z)));
+
+        // Split across the longest side, so chambers stay about as wide as they are tall
+        let horizontal = match chamber.height.cmp(&chamber.width) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => random.bool(),
+        };
+
+        let halves = if horizontal {
+            let rows = random.range(1, chamber.height as u32) as i32;
+            let door = chamber.x + random.range(0, chamber.width as u32) as i32;
+            let wall = self.add_row_wall(maze, &chamber, chamber.y + rows - 1, door, observer);
+            observer.notify(Event::Current(wall));
+
+            [
+                Chamber {
+                    height: rows,
+                    ..chamber
+                },
+                Chamber {
+                    y: chamber.y + rows,
+                    height: chamber.height - rows,
+                    ..chamber
+                },
+            ]
+        } else {
+            let columns = random.range(1, chamber.width as u32) as i32;
+            let door = chamber.y + random.range(0, chamber.height as u32) as i32;
+            let wall =
+                self.add_column_wall(maze, &chamber, chamber.x + columns - 1, door, observer);
+            observer.notify(Event::Current(wall));
+
+            [
+                Chamber {
+                    width: columns,
+                    ..chamber
+                },
+                Chamber {
+                    x: chamber.x + columns,
+                    width: chamber.width - columns,
+                    ..chamber
+                },
+            ]
+        };
+
+        self.chambers
+            .extend(halves.iter().filter(|half| half.can_split()).cloned());
+
+        Ok(())
+    }
+}
+
+/// The corner row based generators lean towards. Every cell gets a passage in one of the two
+/// directions of the corner, so the two borders meeting there end up as long corridors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bias {
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Bias {
+    pub fn all() -> [Bias; 4] {
+        [
+            Bias::NorthEast,
+            Bias::NorthWest,
+            Bias::SouthEast,
+            Bias::SouthWest,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Bias::NorthEast => "north-east",
+            Bias::NorthWest => "north-west",
+            Bias::SouthEast => "south-east",
+            Bias::SouthWest => "south-west",
+        }
+    }
+
+    fn vertical(self) -> Direction {
+        match self {
+            Bias::NorthEast | Bias::NorthWest => Direction::North,
+            Bias::SouthEast | Bias::SouthWest => Direction::South,
+        }
+    }
+
+    fn horizontal(self) -> Direction {
+        match self {
+            Bias::NorthEast | Bias::SouthEast => Direction::East,
+            Bias::NorthWest | Bias::SouthWest => Direction::West,
+        }
+    }
+}
+
+impl FromStr for Bias {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lowercase = s.to_lowercase();
+        Bias::all()
+            .iter()
+            .find(|b| b.name() == lowercase)
+            .cloned()
+            .ok_or_else(|| Error::InvalidParam("bias".to_string(), s.to_string()))
+    }
+}
+
+/// The binary tree algorithm. Every cell gets a passage to one of its two neighbours towards the
+/// corner of the bias, chosen at random, without remembering anything about other cells.
+pub struct BinaryTree {
+    bias: Bias,
+    /// The index of the next cell
+    next: usize,
+    cell_count: usize,
+}
+
+impl BinaryTree {
+    /// Only works on full rectangles of square cells, where every cell has a way to the corner
+    pub fn new(maze: &Maze, bias: Bias) -> Result<BinaryTree> {
+        if !is_rectangle(maze) {
+            return Err(GeneratorType::BinaryTree.unsupported());
+        }
+
+        Ok(BinaryTree {
+            bias,
+            next: 0,
+            cell_count: maze.cell_count(),
+        })
+    }
+}
+
+impl Generator for BinaryTree {
+    fn is_done(&self) -> bool {
+        self.next >= self.cell_count
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if self.next >= self.cell_count {
+            return Ok(());
+        }
+
+        let current = maze.topology().coord(self.next);
+        self.next += 1;
+        observer.notify(Event::Current(vec![current]));
+        observer.notify(Event::CellVisited(current));
+
+        let vertical = straight_neighbour(maze, &current, self.bias.vertical());
+        let horizontal = straight_neighbour(maze, &current, self.bias.horizontal());
+        let direction = match (vertical, horizontal) {
+            (Some(_), Some(_)) if random.bool() => self.bias.vertical(),
+            (Some(_), Some(_)) => self.bias.horizontal(),
+            (Some(_), None) => self.bias.vertical(),
+            (None, Some(_)) => self.bias.horizontal(),
+            (None, None) => return Ok(()),
+        };
+
+        // Going through the wall directly, without looking for the neighbour, keeps this fast
+        // enough for huge mazes
+        let wall = maze.wall(&current, &direction);
+        maze.remove_wall(&wall);
+        observer.notify(Event::PassageCarved(current, current.step(direction)));
+
+        Ok(())
+    }
+}
+
+/// The sidewinder algorithm. Rows are walked towards the horizontal side of the bias, joining
+/// cells into runs. Runs are closed at random by carving a passage towards the vertical side of
+/// the bias from one of their cells. Only the length of the current run is remembered.
+pub struct Sidewinder {
+    bias: Bias,
+    /// The probability of closing the run at every cell
+    close: f32,
+    /// The position of the next cell, in the order they are walked
+    next: usize,
+    cell_count: usize,
+    /// The first and last columns of the maze
+    columns: (i32, i32),
+    /// The number of cells in the current run
+    run: i32,
+}
+
+impl Sidewinder {
+    /// Only works on full rectangles of square cells. `close` has to be between 0 and 1.
+    pub fn new(maze: &Maze, bias: Bias, close: f32) -> Result<Sidewinder> {
+        if !is_rectangle(maze) {
+            return Err(GeneratorType::Sidewinder.unsupported());
+        }
+        if !(0.0..=1.0).contains(&close) {
+            return Err(Error::InvalidParam("close".to_string(), close.to_string()));
+        }
+
+        let topology = maze.topology();
+        let first = topology.coord(0).x;
+        let last = topology.coord(maze.cell_count() - 1).x;
+
+        Ok(Sidewinder {
+            bias,
+            close,
+            next: 0,
+            cell_count: maze.cell_count(),
+            columns: (first, last),
+            run: 0,
+        })
+    }
+
+    /// The cell at position `idx` of the walk, with rows walked backwards when the bias is to
+    /// the west
+    fn walked(&self, maze: &Maze, idx: usize) -> Coord {
+        let coord = maze.topology().coord(idx);
+        match self.bias.horizontal() {
+            Direction::West => Coord {
+                x: self.columns.0 + self.columns.1 - coord.x,
+                ..coord
+            },
+            _ => coord,
+        }
+    }
+}
+
+impl Generator for Sidewinder {
+    fn is_done(&self) -> bool {
+        self.next >= self.cell_count
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if self.next >= self.cell_count {
+            return Ok(());
+        }
+
+        let current = self.walked(maze, self.next);
+        self.next += 1;
+        self.run += 1;
+        observer.notify(Event::Current(vec![current]));
+        observer.notify(Event::CellVisited(current));
+        observer.notify(Event::PathPushed(current));
+
+        let (horizontal, vertical) = (self.bias.horizontal(), self.bias.vertical());
+        let ahead = straight_neighbour(maze, &current, horizontal);
+        let side = straight_neighbour(maze, &current, vertical);
+        let close = match (ahead, side) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(_), Some(_)) => random.unit() < self.close,
+        };
+
+        if let (false, Some(ahead)) = (close, ahead) {
+            let wall = maze.wall(&current, &horizontal);
+            maze.remove_wall(&wall);
+            observer.notify(Event::PassageCarved(current, ahead));
+            return Ok(());
+        }
+
+        if side.is_some() {
+            // The run goes back the way the row is walked
+            let back = random.range(0, self.run as u32) as i32;
+            let dx = current.x - current.step(horizontal).x;
+            let cell = Coord {
+                x: current.x + back * dx,
+                ..current
+            };
+            let wall = maze.wall(&cell, &vertical);
+            maze.remove_wall(&wall);
+            observer.notify(Event::PassageCarved(cell, cell.step(vertical)));
+        }
+
+        self.run = 0;
+        observer.notify(Event::PathUpdated(vec![]));
+
+        Ok(())
+    }
+}
+
+/// How the growing tree algorithm picks the cell to grow from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    Newest,
+    Random,
+    Oldest,
+}
+
+impl Pick {
+    pub fn all() -> [Pick; 3] {
+        [Pick::Newest, Pick::Random, Pick::Oldest]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Pick::Newest => "newest",
+            Pick::Random => "random",
+            Pick::Oldest => "oldest",
+        }
+    }
+}
+
+/// A weighted mix of picks, written as `newest`, `newest/random` for an even mix or
+/// `75% newest / 25% random`, where the percentages have to add up to 100
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Strategy {
+    picks: Vec<(Pick, u32)>,
+}
+
+impl Strategy {
+    pub fn single(pick: Pick) -> Strategy {
+        Strategy {
+            picks: vec![(pick, 1)],
+        }
+    }
+
+    /// The index of the next cell out of `len` active ones, oldest first
+    fn index(&self, random: &mut Random, len: usize) -> usize {
+        let pick = match self.picks.as_slice() {
+            [(pick, _)] => *pick,
+            picks => {
+                let total: u32 = picks.iter().map(|&(_, weight)| weight).sum();
+                let mut roll = random.index(total as usize) as u32;
+                let mut chosen = picks[0].0;
+                for &(pick, weight) in picks {
+                    if roll < weight {
+                        chosen = pick;
+                        break;
+                    }
+                    roll -= weight;
+                }
+                chosen
+            }
+        };
+
+        match pick {
+            Pick::Newest => len - 1,
+            Pick::Random => random.index(len),
+            Pick::Oldest => 0,
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidParam("strategy".to_string(), s.to_string());
+
+        let mut picks = vec![];
+        for part in s.split(&['/', ','][..]) {
+            let (weight, name) = match part.find('%') {
+                Some(idx) => {
+                    let weight = part[..idx].trim().parse().map_err(|_| invalid())?;
+                    (Some(weight), &part[idx + 1..])
+                }
+                None => (None, part),
+            };
+            let name = name.trim().to_lowercase();
+            let pick = Pick::all()
+                .iter()
+                .find(|p| p.name() == name)
+                .cloned()
+                .ok_or_else(invalid)?;
+            picks.push((pick, weight));
+        }
+
+        if picks.iter().all(|(_, weight)| weight.is_none()) {
+            let picks = picks.into_iter().map(|(pick, _)| (pick, 1)).collect();
+            return Ok(Strategy { picks });
+        }
+
+        let picks: Vec<(Pick, u32)> = picks
+            .into_iter()
+            .map(|(pick, weight)| weight.map(|weight| (pick, weight)))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+        let total = picks
+            .iter()
+            .try_fold(0u32, |total, &(_, weight)| total.checked_add(weight));
+        if total != Some(100) {
+            return Err(invalid());
+        }
+
+        Ok(Strategy { picks })
+    }
+}
+
+/// The growing tree algorithm. Cells are grown into a random unvisited neighbour, which is added
+/// to the list of active cells, and dropped from the list once they have none left. Always
+/// picking the newest cell is a depth first search and always picking a random one is close to
+/// Prim's algorithm.
+pub struct GrowingTree {
+    strategy: Strategy,
+    active: VecDeque<Coord>,
+    visited: CellSet,
+}
+
+impl GrowingTree {
+    pub fn new(maze: &Maze, strategy: Strategy) -> GrowingTree {
+        let mut active = VecDeque::new();
+        active.push_back(maze.start);
+
+        GrowingTree {
+            strategy,
+            active,
+            visited: CellSet::new(maze),
+        }
+    }
+}
+
+impl Generator for GrowingTree {
+    fn is_done(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if self.active.is_empty() {
+            return Ok(());
+        }
+
+        let idx = self.strategy.index(random, self.active.len());
+        let cell = self.active[idx];
+        observer.notify(Event::Current(vec![cell]));
+        if self.visited.insert(cell) {
+            observer.notify(Event::CellVisited(cell));
+            observer.notify(Event::FrontierAdded(cell));
+        }
+
+        let neighbours: Vec<_> = maze
+            .linkable_neighbours(&cell)
+            .into_iter()
+            .filter(|(n, _)| !self.visited.contains(n))
+            .collect();
+
+        match random.choose(&neighbours) {
+            Some(&(neighbour, _)) => {
+                maze.link(&cell, &neighbour)?;
+                self.visited.insert(neighbour);
+                self.active.push_back(neighbour);
+                observer.notify(Event::PassageCarved(cell, neighbour));
+                observer.notify(Event::CellVisited(neighbour));
+                observer.notify(Event::FrontierAdded(neighbour));
+            }
+            None => {
+                self.active.remove(idx);
+                observer.notify(Event::FrontierRemoved(cell));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maze::MazeBuilder;
+    use random::{Seed, Stream};
+    use topology::{Grid, Wrap};
+
+    fn placed(width: u32, height: u32, seed: u64) -> Maze {
+        MazeBuilder::new(width, height)
+            .build(&mut Random::new(Seed(seed), Stream::Placement))
+            .unwrap()
+    }
+
+    /// Runs `generator` until it's done
+    fn carved<G: Generator>(maze: &mut Maze, mut generator: G, seed: u64) {
+        let mut random = Random::new(Seed(seed), Stream::Generation);
+        generator
+            .run_for(maze, &mut random, &mut (), Budget::Unlimited)
+            .unwrap();
+    }
+
+    fn generated(generator: GeneratorType, width: u32, height: u32, seed: u64) -> Maze {
+        let mut maze = placed(width, height, seed);
+        let mut random = Random::new(Seed(seed), Stream::Generation);
+        generate(&mut maze, generator, &mut random).unwrap();
+        maze
+    }
+
+    /// Whether every cell can be reached from the start through exactly one path
+    fn is_spanning_tree(maze: &Maze) -> bool {
+        let passages = maze
+            .topology()
+            .edges()
+            .iter()
+            .filter(|wall| !maze.has_wall(wall))
+            .count();
+        let mut reached = CellSet::new(maze);
+        let mut pending = vec![maze.start];
+        reached.insert(maze.start);
+        while let Some(coord) = pending.pop() {
+            for (neighbour, _) in maze.connected_neighbours(&coord) {
+                if reached.insert(neighbour) {
+                    pending.push(neighbour);
+                }
+            }
+        }
+
+        passages + 1 == maze.cell_count() && reached.len() == maze.cell_count()
+    }
+
+    fn is_open(maze: &Maze, coord: Coord, direction: Direction) -> bool {
+        !maze.has_wall(&maze.wall(&coord, &direction))
+    }
+
+    #[test]
+    fn wilson_builds_spanning_trees() {
+        for seed in 0..20 {
+            let maze = generated(GeneratorType::Wilson, 8, 6, seed);
+            assert!(is_spanning_tree(&maze), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn aldous_broder_builds_spanning_trees() {
+        for seed in 0..20 {
+            let maze = generated(GeneratorType::AldousBroder, 8, 6, seed);
+            assert!(is_spanning_tree(&maze), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn recursive_division_builds_spanning_trees() {
+        for seed in 0..20 {
+            let maze = generated(GeneratorType::RecursiveDivision, 8, 6, seed);
+            assert!(is_spanning_tree(&maze), "seed {}", seed);
+        }
+
+        let wrapping = Maze::with_topology(Arc::new(Grid::wrapping(8, 6, Wrap::Cylinder)));
+        match RecursiveDivision::new(&wrapping) {
+            Err(Error::UnsupportedShape(ref name)) => assert_eq!(name, "recursive-division"),
+            _ => panic!("recursive division split a cylinder"),
+        }
+    }
+
+    #[test]
+    fn binary_tree_leans_towards_the_bias() {
+        for &bias in &Bias::all() {
+            for seed in 0..10 {
+                let mut maze = placed(6, 5, seed);
+                let binary_tree = BinaryTree::new(&maze, bias).unwrap();
+                carved(&mut maze, binary_tree, seed);
+                assert!(is_spanning_tree(&maze), "{:?} with seed {}", bias, seed);
+
+                // Every cell but the corner has a single passage towards it
+                for coord in maze.coords() {
+                    let vertical = is_open(&maze, coord, bias.vertical());
+                    let horizontal = is_open(&maze, coord, bias.horizontal());
+                    let corner = maze.neighbour(&coord, &bias.vertical()).is_none()
+                        && maze.neighbour(&coord, &bias.horizontal()).is_none();
+                    assert_eq!(vertical as u32 + horizontal as u32, !corner as u32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sidewinder_runs_follow_the_bias() {
+        for &bias in &Bias::all() {
+            for seed in 0..10 {
+                let mut maze = placed(6, 5, seed);
+                let sidewinder = Sidewinder::new(&maze, bias, 0.5).unwrap();
+                carved(&mut maze, sidewinder, seed);
+                assert!(is_spanning_tree(&maze), "{:?} with seed {}", bias, seed);
+
+                // The row on the vertical side of the bias is a single corridor
+                for coord in maze.coords() {
+                    let last_row = maze.neighbour(&coord, &bias.vertical()).is_none();
+                    let ahead = maze.neighbour(&coord, &bias.horizontal());
+                    if last_row && ahead.is_some() {
+                        assert!(is_open(&maze, coord, bias.horizontal()));
+                    }
+                }
+            }
+        }
+
+        // Always closing runs only leaves passages towards the bias outside the last row
+        let mut maze = placed(6, 5, 0);
+        let sidewinder = Sidewinder::new(&maze, Bias::NorthEast, 1.0).unwrap();
+        carved(&mut maze, sidewinder, 0);
+        for coord in maze.coords().filter(|c| c.y > 0) {
+            assert!(is_open(&maze, coord, Direction::North));
+            assert!(!is_open(&maze, coord, Direction::East));
+        }
+    }
+
+    #[test]
+    fn sidewinder_closes_runs_with_a_probability() {
+        let maze = Maze::new(6, 5);
+        for &close in &[0.0, 0.25, 1.0] {
+            assert!(Sidewinder::new(&maze, Bias::NorthEast, close).is_ok());
+        }
+        for &close in &[-0.1, 1.5, f32::NAN, f32::INFINITY] {
+            match Sidewinder::new(&maze, Bias::NorthEast, close) {
+                Err(Error::InvalidParam(ref name, _)) => assert_eq!(name, "close"),
+                _ => panic!("{} was accepted", close),
+            }
+        }
+
+        let mut params = Params::new();
+        params.set("bias", "north-east");
+        params.set("close", "NaN");
+        let mut random = Random::new(Seed(0), Stream::Generation);
+        assert!(GeneratorType::Sidewinder
+            .init_with(&maze, &mut random, &params)
+            .is_err());
+    }
+
+    fn strategy(s: &str) -> Vec<(Pick, u32)> {
+        s.parse::<Strategy>().unwrap().picks
+    }
+
+    #[test]
+    fn single_picks() {
+        assert_eq!(strategy("newest"), vec![(Pick::Newest, 1)]);
+        assert_eq!(strategy(" Oldest "), vec![(Pick::Oldest, 1)]);
+        assert_eq!(strategy("100% random"), vec![(Pick::Random, 100)]);
+    }
+
+    #[test]
+    fn mixed_picks() {
+        assert_eq!(
+            strategy("75% newest / 25% random"),
+            vec![(Pick::Newest, 75), (Pick::Random, 25)]
+        );
+        assert_eq!(
+            strategy("newest,oldest"),
+            vec![(Pick::Newest, 1), (Pick::Oldest, 1)]
+        );
+    }
+
+    #[test]
+    fn invalid_strategies() {
+        for s in &[
+            "",
+            "fastest",
+            "50% newest",
+            "75% newest / random",
+            "60% newest / 60% random",
+            // Percentages that wrap around to 0 and 100
+            "4294967295% newest, 1% random",
+            "4294967295% newest, 101% random",
+            "x% newest",
+            "newest /",
+        ] {
+            match s.parse::<Strategy>() {
+                Err(Error::InvalidParam(ref name, ref value)) => {
+                    assert_eq!((name.as_str(), value.as_str()), ("strategy", *s))
+                }
+                other => panic!("{:?} parsed as {:?}", s, other),
+            }
+        }
+    }
+
+    #[test]
+    fn picked_indices() {
+        let mut random = Random::new(Seed(1), Stream::Generation);
+        assert_eq!(Strategy::single(Pick::Newest).index(&mut random, 5), 4);
+        assert_eq!(Strategy::single(Pick::Oldest).index(&mut random, 5), 0);
+        let mixed: Strategy = "50% newest / 50% oldest".parse().unwrap();
+        let indices: Vec<_> = (0..100).map(|_| mixed.index(&mut random, 5)).collect();
+        assert!(indices.iter().all(|&idx| idx == 0 || idx == 4));
+        assert!(indices.contains(&0) && indices.contains(&4));
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+use config::Config;
+use maze::{Budget, Error, Maze, Random, Registry, Result, Seed, Solution, Stream};
+
+const EXIT_SUCCESS: i32 = 0;
+/// The maze couldn't be generated or solved
+const EXIT_ERROR: i32 = 1;
+/// The maze was generated but the solver found no path from the start to the end
+const EXIT_UNSOLVABLE: i32 = 2;
+/// The maze couldn't be written to the output file
+const EXIT_OUTPUT: i32 = 3;
+
+/// Generates and solves the maze without opening a window, returning the exit code
+pub fn run(config: &Config) -> i32 {
+    let seed = config.seed().unwrap_or_else(Seed::random);
+    println!("Seed: {}", seed);
+
+    let (maze, solution) = match generate_and_solve(config, seed) {
+        Ok(result) => result,
+        Err(Error::ImpossibleMaze) => {
+            eprintln!("[ERROR] The maze has no solution");
+            return EXIT_UNSOLVABLE;
+        }
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    if let Some(path) = config.output() {
+        let svg = path.extension().and_then(|e| e.to_str()) == Some("svg");
+        let contents = if svg {
+            maze.to_svg(&solution.path, config.cell_width() as f32)
+        } else {
+            match maze.to_ascii(&solution.path) {
+                Ok(ascii) => ascii,
+                Err(e) => {
+                    eprintln!("[ERROR] {}, use an .svg file instead", e);
+                    return EXIT_OUTPUT;
+                }
+            }
+        };
+
+        let written = File::create(path).and_then(|mut f| f.write_all(contents.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("[ERROR] Couldn't write {}: {}", path.display(), e);
+            return EXIT_OUTPUT;
+        }
+        println!("Maze written to {}", path.display());
+    }
+
+    EXIT_SUCCESS
+}
+
+fn generate_and_solve(config: &Config, seed: Seed) -> Result<(Maze, Solution)> {
+    let registry = Registry::default();
+    let mut random = Random::new(seed, Stream::Generation);
+    let mut maze = config
+        .maze_builder(seed)?
+        .build(&mut Random::new(seed, Stream::Placement))?;
+
+    let mut generator = registry.generator(config.generator())?.init(
+        &maze,
+        &mut random,
+        &config.generator_params(),
+    )?;
+    let started = Instant::now();
+    generator.run_for(&mut maze, &mut random, &mut (), Budget::Unlimited)?;
+    println!("Generator: {}", config.generator());
+    println!("Gen time: {:?}", started.elapsed());
+
+    let mut solver = registry
+        .solver(config.solver())?
+        .init(&maze, &config.solver_params())?;
+    let started = Instant::now();
+    let solution = solver.solve(&maze, &mut ())?;
+    println!("Solver: {}", config.solver());
+    println!("Solve time: {:?}", started.elapsed());
+    println!("Solution length: {}", solution.len());
+    println!("Explored cells: {}", solution.explored);
+
+    Ok((maze, solution))
+}
@@ -0,0 +1,38 @@
+//! Maze generation and solving.
+//!
+//! The maze model, every generator and every solver live in this crate. The
+//! ggez based rendering is only available with the `visualizer` feature.
+
+#[cfg(feature = "visualizer")]
+extern crate ggez;
+extern crate rand;
+
+pub mod bitset;
+pub mod budget;
+pub mod error;
+pub mod event;
+pub mod generator;
+pub mod mask;
+pub mod maze;
+pub mod random;
+pub mod registry;
+#[cfg(feature = "visualizer")]
+pub mod render;
+pub mod solver;
+pub mod topology;
+pub mod zoned;
+
+pub use bitset::CellSet;
+pub use budget::Budget;
+pub use error::{Error, Result};
+pub use event::{Event, Observer};
+pub use generator::{generate, generate_with, Generator, GeneratorType};
+pub use mask::Mask;
+pub use maze::{Coord, Direction, Maze, MazeBuilder, Wall};
+pub use random::{Random, Seed, Stream};
+pub use registry::{Param, Params, Registry};
+pub use solver::{solve, solve_with, Solution, Solver, SolverType};
+pub use topology::{
+    Delta, Grid, Hex, Masked, Polar, Topology, TopologyType, Upsilon, Voronoi, Weave, Wrap,
+};
+pub use zoned::{ZoneLayout, Zoned};
@@ -0,0 +1,276 @@
+use std;
+use std::fs;
+use std::path::Path;
+
+use error::{Error, Result};
+use maze::Coord;
+
+/// Which cells of a rectangle are part of a maze
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    width: u32,
+    height: u32,
+    cells: Vec<bool>,
+}
+
+impl Mask {
+    /// A mask where every cell is part of the maze
+    pub fn new(width: u32, height: u32) -> Mask {
+        Mask {
+            width,
+            height,
+            cells: vec![true; width as usize * height as usize],
+        }
+    }
+
+    /// Every line is a row of cells. Spaces and `.` are left out of the maze, any other
+    /// character is a cell.
+    pub fn from_ascii(text: &str) -> Result<Mask> {
+        let rows: Vec<Vec<char>> = text
+            .trim_end()
+            .lines()
+            .map(|line| line.trim_end().chars().collect())
+            .collect();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if width == 0 {
+            return Err(Error::InvalidMask("the mask is empty".to_string()));
+        }
+
+        let mut mask = Mask::new(width as u32, rows.len() as u32);
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..width {
+                let cell = matches!(row.get(x), Some(c) if *c != ' ' && *c != '.');
+                mask.set([x as i32, y as i32].into(), cell);
+            }
+        }
+
+        Ok(mask)
+    }
+
+    /// A black and white image in the plain (`P1`) or raw (`P4`) PBM format. Black pixels are
+    /// cells, white ones are left out of the maze.
+    pub fn from_pbm(data: &[u8]) -> Result<Mask> {
+        let invalid = |reason: &str| Error::InvalidMask(reason.to_string());
+        let mut header = PbmHeader { data, pos: 0 };
+
+        let magic = header.token().ok_or_else(|| invalid("not a PBM image"))?;
+        let width = header
+            .number()
+            .ok_or_else(|| invalid("missing image width"))?;
+        let height = header
+            .number()
+            .ok_or_else(|| invalid("missing image height"))?;
+        // The size comes from the file, so it is checked against the pixels there actually are
+        // before allocating anything
+        let truncated = || invalid("truncated image");
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .ok_or_else(truncated)?;
+
+        match magic {
+            b"P1" => {
+                let cells: Vec<bool> = data[header.pos..]
+                    .iter()
+                    .filter(|b| **b == b'0' || **b == b'1')
+                    .take(len)
+                    .map(|b| *b == b'1')
+                    .collect();
+                if cells.len() < len {
+                    return Err(truncated());
+                }
+
+                Ok(Mask {
+                    width,
+                    height,
+                    cells,
+                })
+            }
+            b"P4" => {
+                // A single whitespace separates the header from the pixels
+                let pixels = data.get(header.pos + 1..).unwrap_or(&[]);
+                let row_len = (width as usize).div_ceil(8);
+                match row_len.checked_mul(height as usize) {
+                    Some(len) if len <= pixels.len() => {}
+                    _ => return Err(truncated()),
+                }
+
+                let mut mask = Mask::new(width, height);
+                for y in 0..height as usize {
+                    for x in 0..width as usize {
+                        let byte = pixels[y * row_len + x / 8];
+                        mask.cells[y * width as usize + x] = byte & (0x80 >> (x % 8)) != 0;
+                    }
+                }
+
+                Ok(mask)
+            }
+            _ => Err(invalid("only P1 and P4 PBM images are supported")),
+        }
+    }
+
+    /// Files ending in `.pbm` are read as images, anything else as text
+    pub fn load(path: &Path) -> Result<Mask> {
+        let data = fs::read(path)
+            .map_err(|e| Error::InvalidMask(format!("couldn't read {}: {}", path.display(), e)))?;
+
+        if path.extension().and_then(|e| e.to_str()) == Some("pbm") {
+            Mask::from_pbm(&data)
+        } else {
+            let text = String::from_utf8(data)
+                .map_err(|_| Error::InvalidMask(format!("{} isn't text", path.display())))?;
+            Mask::from_ascii(&text)
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        if coord.x >= 0
+            && coord.x < self.width as i32
+            && coord.y >= 0
+            && coord.y < self.height as i32
+            && coord.z == 0
+        {
+            Some(coord.y as usize * self.width as usize + coord.x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a cell is part of the maze
+    pub fn contains(&self, coord: &Coord) -> bool {
+        match self.index(coord) {
+            Some(idx) => self.cells[idx],
+            None => false,
+        }
+    }
+
+    pub fn set(&mut self, coord: Coord, cell: bool) {
+        if let Some(idx) = self.index(&coord) {
+            self.cells[idx] = cell;
+        }
+    }
+
+    /// The number of cells that are part of the maze
+    pub fn count(&self) -> usize {
+        self.cells.iter().filter(|cell| **cell).count()
+    }
+}
+
+/// Reads the whitespace separated header of a PBM image, skipping comments
+struct PbmHeader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PbmHeader<'a> {
+    fn token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(b'#') => {
+                    while matches!(self.data.get(self.pos), Some(b) if *b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+                None => return None,
+            }
+        }
+
+        let start = self.pos;
+        while matches!(self.data.get(self.pos), Some(b) if !b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+
+        Some(&self.data[start..self.pos])
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let token = self.token()?;
+        std::str::from_utf8(token).ok()?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(mask: &Mask) -> Vec<String> {
+        (0..mask.height() as i32)
+            .map(|y| {
+                (0..mask.width() as i32)
+                    .map(|x| {
+                        if mask.contains(&[x, y].into()) {
+                            'X'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ascii_masks() {
+        let mask = Mask::from_ascii("XX o\n.X\n X X  \n\n").unwrap();
+        assert_eq!(cells(&mask), vec!["XX.X", ".X..", ".X.X"]);
+        assert_eq!(mask.count(), 6);
+    }
+
+    #[test]
+    fn empty_ascii_masks() {
+        for text in &["", "\n\n", "   \n  "] {
+            assert!(Mask::from_ascii(text).is_err(), "{:?}", text);
+        }
+        // Only spaces and dots, but still a size
+        assert_eq!(Mask::from_ascii("..\n..").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn plain_pbm_masks() {
+        let data = b"P1\n# a comment\n3 2\n1 0 1\n011\n";
+        let mask = Mask::from_pbm(data).unwrap();
+        assert_eq!(cells(&mask), vec!["X.X", ".XX"]);
+    }
+
+    #[test]
+    fn raw_pbm_masks() {
+        // Rows are padded to whole bytes
+        let mut data = b"P4 10 2\n".to_vec();
+        data.extend_from_slice(&[0b1000_0000, 0b0100_0000, 0b0000_0001, 0b1000_0000]);
+        let mask = Mask::from_pbm(&data).unwrap();
+        assert_eq!(cells(&mask), vec!["X........X", ".......XX."]);
+    }
+
+    #[test]
+    fn invalid_pbm_masks() {
+        let invalid: &[&[u8]] = &[
+            b"",
+            b"P2\n1 1\n1\n",
+            b"P1\n3\n",
+            b"P1\nthree 2\n",
+            b"P1\n3 2\n1 0 1\n0\n",
+            b"P4\n10 2\n\x80",
+            // Far more pixels than the file has
+            b"P1 4294967295 4294967295\n1",
+            b"P4 4294967295 4294967295\n\x80",
+            b"P4 8 4294967295\n\x80",
+        ];
+        for data in invalid {
+            match Mask::from_pbm(data) {
+                Err(Error::InvalidMask(_)) => {}
+                other => panic!("{:?} parsed as {:?}", data, other),
+            }
+        }
+    }
+}
@@ -1,69 +1,98 @@
 use std;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fmt;
 use std::num::ParseIntError;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use ggez::graphics;
-use ggez::graphics::Color;
-use ggez::graphics::MeshBuilder;
-use ggez::mint::Point2;
-use ggez::{Context, GameResult};
-use rand::{Rng, StdRng};
-
-use config::{
-    Config, CELL_WALL_WIDTH, COLOR_END, COLOR_EXPLORED, COLOR_HIGHLIGHT_BRIGHT,
-    COLOR_HIGHLIGHT_DARK, COLOR_HIGHLIGHT_MEDIUM, COLOR_START, COLOR_WALL,
-};
+use bitset::{BitSet, CellSet};
 use error::{Error, Result};
+use random::Random;
+use topology::{Grid, Topology};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Orientation {
-    Horizontal,
-    Vertical,
-}
-
-impl fmt::Display for Orientation {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Orientation::Horizontal => write!(f, "Horizontal"),
-            Orientation::Vertical => write!(f, "Vertical"),
-        }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Direction {
     North,
+    NorthEast,
     East,
+    SouthEast,
     South,
+    SouthWest,
     West,
+    NorthWest,
+    /// Towards the centre of a circular maze
+    Inward,
+    /// Away from the centre of a circular maze. Cells can have several neighbours in this
+    /// direction, told apart by their index.
+    Outward(u32),
+    Clockwise,
+    CounterClockwise,
+    /// To the floor above, in mazes with several floors
+    Up,
+    /// To the floor below, in mazes with several floors
+    Down,
+    /// Under the neighbour to the north, to the cell past it, in weave mazes
+    UnderNorth,
+    /// Under the neighbour to the east, to the cell past it, in weave mazes
+    UnderEast,
+    /// Under the neighbour to the south, to the cell past it, in weave mazes
+    UnderSouth,
+    /// Under the neighbour to the west, to the cell past it, in weave mazes
+    UnderWest,
+    /// One of the sides of an irregular cell, counted clockwise. The cell on the other side of
+    /// it has its own number for the same side.
+    Side(u32),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Point {
-    pub x: i32,
-    pub y: i32,
-}
-
-impl Point {
-    fn into_coord(self, cell_width: u32, cell_height: u32) -> Coord {
-        Coord {
-            x: (self.x - cell_width as i32 / 2) / cell_width as i32,
-            y: (self.y - cell_height as i32 / 2) / cell_height as i32,
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::East => Direction::West,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::South => Direction::North,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::West => Direction::East,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::Inward => Direction::Outward(0),
+            Direction::Outward(_) => Direction::Inward,
+            Direction::Clockwise => Direction::CounterClockwise,
+            Direction::CounterClockwise => Direction::Clockwise,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::UnderNorth => Direction::UnderSouth,
+            Direction::UnderEast => Direction::UnderWest,
+            Direction::UnderSouth => Direction::UnderNorth,
+            Direction::UnderWest => Direction::UnderEast,
+            Direction::Side(idx) => Direction::Side(idx),
         }
     }
 }
 
-impl From<[i32; 2]> for Point {
-    fn from(a: [i32; 2]) -> Point {
-        Point { x: a[0], y: a[1] }
-    }
-}
-
-impl fmt::Display for Point {
+impl fmt::Display for Direction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        match *self {
+            Direction::North => write!(f, "North"),
+            Direction::NorthEast => write!(f, "North East"),
+            Direction::East => write!(f, "East"),
+            Direction::SouthEast => write!(f, "South East"),
+            Direction::South => write!(f, "South"),
+            Direction::SouthWest => write!(f, "South West"),
+            Direction::West => write!(f, "West"),
+            Direction::NorthWest => write!(f, "North West"),
+            Direction::Inward => write!(f, "Inward"),
+            Direction::Outward(idx) => write!(f, "Outward {}", idx),
+            Direction::Clockwise => write!(f, "Clockwise"),
+            Direction::CounterClockwise => write!(f, "Counter Clockwise"),
+            Direction::Up => write!(f, "Up"),
+            Direction::Down => write!(f, "Down"),
+            Direction::UnderNorth => write!(f, "Under North"),
+            Direction::UnderEast => write!(f, "Under East"),
+            Direction::UnderSouth => write!(f, "Under South"),
+            Direction::UnderWest => write!(f, "Under West"),
+            Direction::Side(idx) => write!(f, "Side {}", idx),
+        }
     }
 }
 
@@ -71,164 +100,74 @@ impl fmt::Display for Point {
 pub struct Coord {
     pub x: i32,
     pub y: i32,
+    /// The floor of the cell, always 0 in mazes with a single floor
+    pub z: i32,
 }
 
 impl Coord {
-    pub fn random(max_x: u32, max_y: u32, random: &mut StdRng) -> Coord {
+    pub fn random(max_x: u32, max_y: u32, random: &mut Random) -> Coord {
         Coord {
-            x: random.gen_range(0, max_x) as i32,
-            y: random.gen_range(0, max_y) as i32,
+            x: random.range(0, max_x) as i32,
+            y: random.range(0, max_y) as i32,
+            z: 0,
         }
     }
 
     pub fn manhattan_dist(&self, other: &Coord) -> u32 {
-        ((self.x - other.x).abs() + (self.y - other.y).abs()) as u32
-    }
-
-    pub fn walls(&self, config: &Config) -> [Wall; 4] {
-        [
-            self.north_wall(config),
-            self.east_wall(config),
-            self.south_wall(config),
-            self.west_wall(config),
-        ]
-    }
-
-    pub fn wall(&self, direction: &Direction, config: &Config) -> Wall {
-        match direction {
-            Direction::North => self.north_wall(config),
-            Direction::East => self.east_wall(config),
-            Direction::South => self.south_wall(config),
-            Direction::West => self.west_wall(config),
-        }
-    }
-
-    pub fn north_wall(&self, config: &Config) -> Wall {
-        let as_point: Point = self.into_point(config.cell_width(), config.cell_height());
-        let offset = if config.cell_height() % 2 == 0 {
-            config.cell_height() as i32 / 2
-        } else {
-            (config.cell_height() + 1) as i32 / 2
+        ((self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()) as u32
+    }
+
+    /// The coordinate one step away in the given direction on a square grid, whether or not it
+    /// is inside the maze. Circular directions move along rows (rings) and columns, sides of
+    /// irregular cells stay in place.
+    pub fn step(&self, direction: Direction) -> Coord {
+        let (dx, dy, dz) = match direction {
+            Direction::North => (0, -1, 0),
+            Direction::NorthEast => (1, -1, 0),
+            Direction::East => (1, 0, 0),
+            Direction::SouthEast => (1, 1, 0),
+            Direction::South => (0, 1, 0),
+            Direction::SouthWest => (-1, 1, 0),
+            Direction::West => (-1, 0, 0),
+            Direction::NorthWest => (-1, -1, 0),
+            Direction::Inward => (0, -1, 0),
+            Direction::Outward(_) => (0, 1, 0),
+            Direction::Clockwise => (1, 0, 0),
+            Direction::CounterClockwise => (-1, 0, 0),
+            Direction::Up => (0, 0, 1),
+            Direction::Down => (0, 0, -1),
+            Direction::UnderNorth => (0, -2, 0),
+            Direction::UnderEast => (2, 0, 0),
+            Direction::UnderSouth => (0, 2, 0),
+            Direction::UnderWest => (-2, 0, 0),
+            Direction::Side(_) => (0, 0, 0),
         };
-        Wall {
-            center: [as_point.x, as_point.y - offset].into(),
-            orientation: Orientation::Horizontal,
-            border: self.y == 0,
-            size: config.cell_height(),
-        }
-    }
 
-    pub fn east_wall(&self, config: &Config) -> Wall {
-        let as_point: Point = self.into_point(config.cell_width(), config.cell_height());
-        Wall {
-            center: [as_point.x + config.cell_width() as i32 / 2, as_point.y].into(),
-            orientation: Orientation::Vertical,
-            border: self.x == (config.maze_width() - 1) as i32,
-            size: config.cell_width(),
+        Coord {
+            x: self.x + dx,
+            y: self.y + dy,
+            z: self.z + dz,
         }
     }
+}
 
-    pub fn south_wall(&self, config: &Config) -> Wall {
-        let as_point: Point = self.into_point(config.cell_width(), config.cell_height());
-        Wall {
-            center: [as_point.x, as_point.y + config.cell_height() as i32 / 2].into(),
-            orientation: Orientation::Horizontal,
-            border: self.y == (config.maze_height() - 1) as i32,
-            size: config.cell_height(),
+impl From<[i32; 2]> for Coord {
+    fn from(a: [i32; 2]) -> Coord {
+        Coord {
+            x: a[0],
+            y: a[1],
+            z: 0,
         }
     }
-
-    pub fn west_wall(&self, config: &Config) -> Wall {
-        let as_point: Point = self.into_point(config.cell_width(), config.cell_height());
-        let offset = if config.cell_width() % 2 == 0 {
-            config.cell_width() as i32 / 2
-        } else {
-            (config.cell_width() + 1) as i32 / 2
-        };
-
-        Wall {
-            center: [as_point.x - offset, as_point.y].into(),
-            orientation: Orientation::Vertical,
-            border: self.x == 0,
-            size: config.cell_width(),
-        }
-    }
-
-    pub fn into_point(self, cell_width: u32, cell_height: u32) -> Point {
-        Point {
-            x: self.x * cell_width as i32 + cell_width as i32 / 2,
-            y: self.y * cell_height as i32 + cell_height as i32 / 2,
-        }
-    }
-
-    pub fn valid_coord(&self, maze_width: u32, maze_height: u32) -> bool {
-        self.x >= 0
-            && self.x <= (maze_width - 1) as i32
-            && self.y >= 0
-            && self.y <= (maze_height - 1) as i32
-    }
-
-    pub fn neighbour(
-        &self,
-        direction: &Direction,
-        maze_width: u32,
-        maze_height: u32,
-    ) -> Option<Coord> {
-        let candidate = match direction {
-            Direction::North => Coord {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Direction::East => Coord {
-                x: self.x + 1,
-                y: self.y,
-            },
-            Direction::South => Coord {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Direction::West => Coord {
-                x: self.x - 1,
-                y: self.y,
-            },
-        };
-
-        if candidate.valid_coord(maze_width, maze_height) {
-            Some(candidate)
-        } else {
-            None
-        }
-    }
-
-    pub fn neighbours(&self, maze_width: u32, maze_height: u32) -> Vec<(Coord, Direction)> {
-        vec![
-            (
-                self.neighbour(&Direction::North, maze_width, maze_height),
-                Direction::North,
-            ),
-            (
-                self.neighbour(&Direction::East, maze_width, maze_height),
-                Direction::East,
-            ),
-            (
-                self.neighbour(&Direction::South, maze_width, maze_height),
-                Direction::South,
-            ),
-            (
-                self.neighbour(&Direction::West, maze_width, maze_height),
-                Direction::West,
-            ),
-        ]
-        .into_iter()
-        .filter(|(coord, _)| coord.is_some())
-        .map(|(c, d)| (c.unwrap(), d))
-        .collect()
-    }
 }
 
-impl From<[i32; 2]> for Coord {
-    fn from(a: [i32; 2]) -> Coord {
-        Coord { x: a[0], y: a[1] }
+impl From<[i32; 3]> for Coord {
+    fn from(a: [i32; 3]) -> Coord {
+        Coord {
+            x: a[0],
+            y: a[1],
+            z: a[2],
+        }
     }
 }
 
@@ -237,6 +176,7 @@ impl From<[u32; 2]> for Coord {
         Coord {
             x: a[0] as i32,
             y: a[1] as i32,
+            z: 0,
         }
     }
 }
@@ -252,287 +192,226 @@ impl FromStr for Coord {
 
         let x_fromstr = coords[0].parse::<i32>()?;
         let y_fromstr = coords[1].parse::<i32>()?;
+        let z_fromstr = match coords.get(2) {
+            Some(z) => z.parse::<i32>()?,
+            None => 0,
+        };
 
         Ok(Coord {
             x: x_fromstr,
             y: y_fromstr,
+            z: z_fromstr,
         })
     }
 }
 
 impl fmt::Display for Coord {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        if self.z == 0 {
+            write!(f, "({}, {})", self.x, self.y)
+        } else {
+            write!(f, "({}, {}, {})", self.x, self.y, self.z)
+        }
     }
 }
 
+/// A wall between two cells, identified by the cell it belongs to and its direction.
+///
+/// The `Topology` of the maze decides which of the two cells a wall belongs to, so the same wall
+/// seen from either side is always the same `Wall`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Wall {
-    center: Point,
-    orientation: Orientation,
+    coord: Coord,
+    direction: Direction,
+    other: Coord,
     border: bool,
-    size: u32,
 }
 
 impl Wall {
+    /// The wall of `coord` in `direction`, dividing it from `other`
+    pub fn new(coord: Coord, direction: Direction, other: Coord, border: bool) -> Wall {
+        Wall {
+            coord,
+            direction,
+            other,
+            border,
+        }
+    }
+
+    #[inline]
+    pub fn coord(&self) -> Coord {
+        self.coord
+    }
+
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
     pub fn removable(&self) -> bool {
         !self.border
     }
 
-    fn build_mesh(&self, mb: &mut MeshBuilder, color: Color) {
-        let offset = if self.size % 2 == 0 { 0.0 } else { 1.0 };
-
-        let (start, end) = match self.orientation {
-            Orientation::Horizontal => (
-                Point2 {
-                    x: self.center.x as f32 - (self.size as f32 + offset) / 2.0,
-                    y: self.center.y as f32,
-                },
-                Point2 {
-                    x: self.center.x as f32 + self.size as f32 / 2.0,
-                    y: self.center.y as f32,
-                },
-            ),
-            Orientation::Vertical => (
-                Point2 {
-                    x: self.center.x as f32,
-                    y: self.center.y as f32 - (self.size as f32 + offset) / 2.0,
-                },
-                Point2 {
-                    x: self.center.x as f32,
-                    y: self.center.y as f32 + self.size as f32 / 2.0,
-                },
-            ),
-        };
-
-        mb.line(&[start, end], CELL_WALL_WIDTH, color).unwrap();
+    /// The two cells divided by this wall. For border walls the second one is outside the maze.
+    pub fn divided_coords(&self) -> (Coord, Coord) {
+        (self.coord, self.other)
     }
 }
 
 impl fmt::Display for Wall {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "[Wall {} @ {}]", self.orientation, self.center)
+        write!(f, "[Wall {} of {}]", self.direction, self.coord)
     }
 }
 
+/// Builds a `Maze`. The start and end are placed where the topology wants them when they're not
+/// provided, or randomly if it doesn't mind.
 #[derive(Debug, Clone)]
-pub struct Cell {
-    center: Point,
-    width: f32,
-    height: f32,
+pub struct MazeBuilder {
+    topology: Arc<dyn Topology>,
+    start: Option<Coord>,
+    end: Option<Coord>,
 }
 
-impl Cell {
-    pub fn new(center: Point, width: u32, height: u32) -> Cell {
-        Cell {
-            center,
-            width: width as f32,
-            height: height as f32,
-        }
-    }
-
-    pub fn build_mesh(&self, mb: &mut MeshBuilder, color: Color) {
-        use graphics::DrawMode;
-        let x1 = self.center.x as f32 - self.width / 2.0;
-        let x2 = self.center.x as f32 + self.width / 2.0;
-        let y1 = self.center.y as f32 - self.height / 2.0;
-        let y2 = self.center.y as f32 + self.height / 2.0;
-        mb.polygon(
-            DrawMode::fill(),
-            &[
-                Point2 { x: x1, y: y1 },
-                Point2 { x: x2, y: y1 },
-                Point2 { x: x2, y: y2 },
-                Point2 { x: x1, y: y2 },
-            ],
-            color,
-        )
-        .unwrap();
+impl MazeBuilder {
+    /// A builder of `width` x `height` rectangular mazes
+    pub fn new(width: u32, height: u32) -> MazeBuilder {
+        MazeBuilder::with_topology(Arc::new(Grid::new(width, height)))
     }
-}
-
-#[derive(Debug)]
-pub struct Maze<'a> {
-    config: &'a Config,
-    pub walls: HashSet<Wall>,
-    pub cells: HashMap<Coord, Cell>,
-    pub start: Coord,
-    pub end: Coord,
-    pub explored: HashSet<Coord>,
-    pub highlight_bright: HashSet<Coord>,
-    pub highlight_medium: HashSet<Coord>,
-    pub highlight_dark: HashSet<Coord>,
-}
 
-impl<'a> Maze<'a> {
-    pub fn new<'b>(config: &'a Config, random: &'b mut StdRng) -> Maze<'a> {
-        let mut walls = HashSet::new();
-        let mut cells = HashMap::new();
+    pub fn with_topology(topology: Arc<dyn Topology>) -> MazeBuilder {
+        MazeBuilder {
+            topology,
+            start: None,
+            end: None,
+        }
+    }
 
-        for y in 0..config.maze_height() {
-            for x in 0..config.maze_width() {
-                let coord: Coord = [x, y].into();
+    pub fn start(mut self, start: Coord) -> MazeBuilder {
+        self.start = Some(start);
+        self
+    }
 
-                for wall in &coord.walls(config) {
-                    walls.insert(*wall);
-                }
+    pub fn end(mut self, end: Coord) -> MazeBuilder {
+        self.end = Some(end);
+        self
+    }
 
-                let cell = Cell::new(
-                    coord.into_point(config.cell_width(), config.cell_height()),
-                    config.cell_width(),
-                    config.cell_height(),
-                );
+    fn random_coord(&self, random: &mut Random) -> Coord {
+        let idx = random.index(self.topology.cell_count());
+        self.topology.coord(idx)
+    }
 
-                cells.insert(coord, cell);
-            }
+    pub fn build(self, random: &mut Random) -> Result<Maze> {
+        if self.topology.cell_count() < 2 {
+            return Err(Error::TooFewCells(self.topology.cell_count()));
         }
 
-        let start = match config.start() {
-            Some(coord) => coord,
-            None => Coord::random(config.maze_width(), config.maze_height(), random),
+        let entrances = self.topology.entrances();
+        let mut start = match (self.start, entrances) {
+            (Some(coord), _) => coord,
+            (None, Some((start, _))) if self.end != Some(start) => start,
+            _ => self.random_coord(random),
         };
-        let mut end = match config.end() {
-            Some(coord) => coord,
-            None => Coord::random(config.maze_width(), config.maze_height(), random),
+        let mut end = match (self.end, entrances) {
+            (Some(coord), _) => coord,
+            (None, Some((_, end))) if end != start => end,
+            _ => self.random_coord(random),
         };
 
+        // Whichever side wasn't given is rolled again
         while start == end {
-            end = Coord::random(config.maze_width(), config.maze_height(), random);
-        }
-
-        let explored = HashSet::new();
-
-        Maze {
-            config,
-            walls,
-            cells,
-            start,
-            end,
-            explored,
-            highlight_bright: HashSet::new(),
-            highlight_medium: HashSet::new(),
-            highlight_dark: HashSet::new(),
-        }
-    }
-
-    pub fn render(&self, ctx: &mut Context) -> GameResult<()> {
-        let mut start_mb = MeshBuilder::new();
-        let mut end_mb = MeshBuilder::new();
-        let mut highlight_bright_mb = MeshBuilder::new();
-        let mut highlight_medium_mb = MeshBuilder::new();
-        let mut highlight_dark_mb = MeshBuilder::new();
-        let mut explored_mb = MeshBuilder::new();
-
-        for (coord, cell) in &self.cells {
-            if *coord == self.start {
-                cell.build_mesh(&mut start_mb, COLOR_START.into());
-            } else if *coord == self.end {
-                cell.build_mesh(&mut end_mb, COLOR_END.into());
-            } else if self.highlight_bright.contains(&coord) {
-                cell.build_mesh(&mut highlight_bright_mb, COLOR_HIGHLIGHT_BRIGHT.into());
-            } else if self.highlight_medium.contains(&coord) {
-                cell.build_mesh(&mut highlight_medium_mb, COLOR_HIGHLIGHT_MEDIUM.into());
-            } else if self.highlight_dark.contains(&coord) {
-                cell.build_mesh(&mut highlight_dark_mb, COLOR_HIGHLIGHT_DARK.into());
-            } else if self.explored.contains(&coord) {
-                cell.build_mesh(&mut explored_mb, COLOR_EXPLORED.into());
+            match (self.start, self.end) {
+                (_, None) => end = self.random_coord(random),
+                (None, Some(_)) => start = self.random_coord(random),
+                (Some(_), Some(_)) => break,
             }
         }
 
-        if let Ok(m) = start_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
-        }
-        if let Ok(m) = end_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
-        }
-        if let Ok(m) = highlight_bright_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
-        }
-        if let Ok(m) = highlight_medium_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
-        }
-        if let Ok(m) = highlight_dark_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        for coord in &[start, end] {
+            if self.topology.index(coord).is_none() {
+                return Err(Error::OutOfBounds(*coord));
+            }
         }
-        if let Ok(m) = explored_mb.build(ctx) {
-            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        if start == end {
+            return Err(Error::StartIsEnd(start));
         }
 
-        // Use a list of mesh builder because we might run into vertex buffer limits
-        let mut walls_mbs = self
-            .walls
-            .iter()
-            .collect::<Vec<_>>()
-            .chunks(10_000)
-            .map(|walls| {
-                let mut mb = MeshBuilder::new();
-                for wall in walls {
-                    wall.build_mesh(&mut mb, COLOR_WALL.into());
-                }
-                mb
-            })
-            .collect::<Vec<_>>();
+        let mut maze = Maze::with_topology(self.topology);
+        maze.start = start;
+        maze.end = end;
 
-        for mb in &mut walls_mbs {
-            let mesh = mb.build(ctx)?;
-            graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
-        }
+        Ok(maze)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Maze {
+    topology: Arc<dyn Topology>,
+    /// One bit per edge of the topology, set when the wall was removed
+    passages: BitSet,
+    pub start: Coord,
+    pub end: Coord,
+}
 
-        Ok(())
+impl Maze {
+    /// A fully walled `width` x `height` rectangular maze, starting at the top left corner and
+    /// ending at the bottom right one
+    pub fn new(width: u32, height: u32) -> Maze {
+        Maze::with_topology(Arc::new(Grid::new(width, height)))
     }
 
-    pub fn divided_coords(&self, wall: &Wall) -> (Coord, Coord) {
-        match wall.orientation {
-            Orientation::Horizontal => {
-                let up: Coord = Point {
-                    y: wall.center.y - self.config.cell_height() as i32 / 2,
-                    ..wall.center
-                }
-                .into_coord(self.config.cell_width(), self.config.cell_height());
-                let down: Coord = Point {
-                    y: wall.center.y + self.config.cell_height() as i32 / 2,
-                    ..wall.center
-                }
-                .into_coord(self.config.cell_width(), self.config.cell_height());
+    /// A `width` x `height` rectangular maze without any walls other than its border, starting
+    /// at the top left corner and ending at the bottom right one
+    pub fn empty(width: u32, height: u32) -> Maze {
+        let mut maze = Maze::new(width, height);
+        maze.remove_all_walls();
+        maze
+    }
 
-                (up, down)
-            }
-            Orientation::Vertical => {
-                let left: Coord = Point {
-                    x: wall.center.x - self.config.cell_width() as i32 / 2,
-                    ..wall.center
-                }
-                .into_coord(self.config.cell_width(), self.config.cell_height());
-                let right: Coord = Point {
-                    x: wall.center.x + self.config.cell_width() as i32 / 2,
-                    ..wall.center
-                }
-                .into_coord(self.config.cell_width(), self.config.cell_height());
+    /// A fully walled maze, starting at the first cell of the topology and ending at the last one
+    pub fn with_topology(topology: Arc<dyn Topology>) -> Maze {
+        let cell_count = topology.cell_count();
 
-                (left, right)
-            }
+        Maze {
+            passages: BitSet::new(topology.edge_count()),
+            start: topology.coord(0),
+            end: topology.coord(cell_count.saturating_sub(1)),
+            topology,
         }
     }
 
+    #[inline]
+    pub fn topology(&self) -> &Arc<dyn Topology> {
+        &self.topology
+    }
+
     pub fn link(&mut self, c1: &Coord, c2: &Coord) -> Result<()> {
-        match c1
-            .neighbours(self.config.maze_width(), self.config.maze_height())
-            .iter()
-            .find(|n| n.0 == *c2)
-        {
+        match self.neighbours(c1).iter().find(|n| n.0 == *c2) {
             Some((_, direction)) => {
-                let wall = match direction {
-                    Direction::North => self.north_wall(c1),
-                    Direction::East => self.east_wall(c1),
-                    Direction::South => self.south_wall(c1),
-                    Direction::West => self.west_wall(c1),
-                };
+                let wall = self.wall(c1, direction);
 
                 if !wall.removable() {
                     return Err(Error::BorderWall(wall));
                 }
+                if !self.can_remove_wall(&wall) {
+                    return Err(Error::CrossedWall(wall));
+                }
+
+                self.remove_wall(&wall);
+
+                Ok(())
+            }
+            None => Err(Error::NotNeighbours(*c1, *c2)),
+        }
+    }
 
-                self.walls.remove(&wall);
+    /// Puts back the wall between two neighbours
+    pub fn unlink(&mut self, c1: &Coord, c2: &Coord) -> Result<()> {
+        match self.neighbours(c1).iter().find(|n| n.0 == *c2) {
+            Some((_, direction)) => {
+                let wall = self.wall(c1, direction);
+                self.add_wall(&wall);
 
                 Ok(())
             }
@@ -540,67 +419,403 @@ impl<'a> Maze<'a> {
         }
     }
 
+    /*
+     * Walls
+     */
+
+    pub fn has_wall(&self, wall: &Wall) -> bool {
+        match self.topology.edge_index(wall) {
+            Some(idx) => !self.passages.get(idx),
+            None => true,
+        }
+    }
+
+    /// Whether a wall can be removed. Border walls can't, and neither can walls that would make
+    /// two passages cross in weave mazes.
+    pub fn can_remove_wall(&self, wall: &Wall) -> bool {
+        wall.removable() && self.topology.can_remove_wall(wall, &|w| self.has_wall(w))
+    }
+
+    /// Removes a wall, returning whether it was present. Walls that can't be removed are left
+    /// alone.
+    pub fn remove_wall(&mut self, wall: &Wall) -> bool {
+        if !self.can_remove_wall(wall) {
+            return false;
+        }
+
+        match self.topology.edge_index(wall) {
+            Some(idx) => !self.passages.set(idx, true),
+            None => false,
+        }
+    }
+
+    /// Puts a wall back, returning whether it was missing
+    pub fn add_wall(&mut self, wall: &Wall) -> bool {
+        match self.topology.edge_index(wall) {
+            Some(idx) => self.passages.set(idx, false),
+            None => false,
+        }
+    }
+
+    /// Removes every wall that can be removed, leaving only the border
+    pub fn remove_all_walls(&mut self) {
+        for wall in self.topology.edges() {
+            self.remove_wall(&wall);
+        }
+    }
+
+    /// All walls still standing, including the maze border
+    pub fn walls<'b>(&'b self) -> impl Iterator<Item = Wall> + 'b {
+        self.coords().flat_map(move |coord| {
+            self.topology
+                .directions(&coord)
+                .into_iter()
+                .map(move |direction| (direction, self.wall(&coord, &direction)))
+                .filter(move |(direction, wall)| {
+                    wall.coord() == coord && wall.direction() == *direction
+                })
+                .map(|(_, wall)| wall)
+                .filter(move |w| self.has_wall(w))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Every line drawn for the walls of the maze, with the wall it belongs to. Walls that a
+    /// tunnel passes under are drawn with a gap, and the tunnel with dashed lines under the cell.
+    pub fn wall_lines(&self) -> Vec<(Wall, Vec<(f32, f32)>)> {
+        let mut lines = vec![];
+        let mut crossed = HashSet::new();
+        for coord in self.coords() {
+            for direction in self.topology.directions(&coord) {
+                let wall = self.wall(&coord, &direction);
+                if wall.coord() != coord || wall.direction() != direction || self.has_wall(&wall) {
+                    continue;
+                }
+
+                let crossings = self.topology.tunnel_crossings(&wall);
+                if !crossings.is_empty() {
+                    crossed.extend(crossings);
+                    for line in self.topology.tunnel_lines(&wall) {
+                        lines.push((wall, line));
+                    }
+                }
+            }
+        }
+
+        for wall in self.walls() {
+            // Walls between floors and tunnels that weren't dug aren't drawn
+            for line in self.topology.wall_lines(&wall) {
+                if line.len() < 2 {
+                    continue;
+                }
+
+                if crossed.contains(&wall) {
+                    let (from, to) = (line[0], line[line.len() - 1]);
+                    let at = |t: f32| (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+                    lines.push((wall, vec![from, at(1.0 / 3.0)]));
+                    lines.push((wall, vec![at(2.0 / 3.0), to]));
+                } else {
+                    lines.push((wall, line));
+                }
+            }
+        }
+
+        lines
+    }
+
     /*
      * Coords
      */
 
-    #[allow(unused)]
-    pub fn walls(&self, coord: &Coord) -> [Wall; 4] {
-        coord.walls(self.config)
+    /// All the cells of the maze, in the order of the topology
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let topology = self.topology.clone();
+        (0..topology.cell_count()).map(move |idx| topology.coord(idx))
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.topology.cell_count()
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        self.topology.index(coord).is_some()
     }
 
     pub fn wall(&self, coord: &Coord, direction: &Direction) -> Wall {
-        coord.wall(direction, self.config)
+        self.topology.wall(coord, *direction)
     }
 
     pub fn north_wall(&self, coord: &Coord) -> Wall {
-        coord.north_wall(self.config)
+        self.wall(coord, &Direction::North)
     }
 
     pub fn east_wall(&self, coord: &Coord) -> Wall {
-        coord.east_wall(self.config)
+        self.wall(coord, &Direction::East)
     }
 
     pub fn south_wall(&self, coord: &Coord) -> Wall {
-        coord.south_wall(self.config)
+        self.wall(coord, &Direction::South)
     }
 
     pub fn west_wall(&self, coord: &Coord) -> Wall {
-        coord.west_wall(self.config)
+        self.wall(coord, &Direction::West)
     }
 
     pub fn neighbour(&self, coord: &Coord, direction: &Direction) -> Option<Coord> {
-        coord.neighbour(
-            direction,
-            self.config.maze_width(),
-            self.config.maze_height(),
-        )
+        self.topology.neighbour(coord, *direction)
     }
 
     pub fn neighbours(&self, coord: &Coord) -> Vec<(Coord, Direction)> {
-        coord.neighbours(self.config.maze_width(), self.config.maze_height())
+        self.topology
+            .directions(coord)
+            .into_iter()
+            .filter_map(|d| self.topology.neighbour(coord, d).map(|n| (n, d)))
+            .collect()
+    }
+
+    /// The neighbours a cell is linked to, or could be linked to right now
+    pub fn linkable_neighbours(&self, coord: &Coord) -> Vec<(Coord, Direction)> {
+        self.neighbours(coord)
+            .into_iter()
+            .filter(|(_, d)| {
+                let wall = self.wall(coord, d);
+                !self.has_wall(&wall) || self.can_remove_wall(&wall)
+            })
+            .collect()
     }
 
     pub fn connected_neighbours(&self, coord: &Coord) -> Vec<(Coord, Direction)> {
-        coord
-            .neighbours(self.config.maze_width(), self.config.maze_height())
+        self.neighbours(coord)
             .into_iter()
             .filter(|(_, d)| {
                 let wall = self.wall(coord, d);
-                !self.walls.contains(&wall)
+                !self.has_wall(&wall)
             })
             .collect()
     }
 
-    /*
-     * Maze config
-     */
+    /// The directions of the stairs in a cell, in mazes with several floors
+    pub fn stairs(&self, coord: &Coord) -> Vec<Direction> {
+        vec![Direction::Up, Direction::Down]
+            .into_iter()
+            .filter(|d| !self.has_wall(&self.wall(coord, d)))
+            .collect()
+    }
+
+    /// Whether every cell has walls to the north, east, south and west and nowhere else
+    pub fn has_square_cells(&self) -> bool {
+        self.coords().all(|c| {
+            let directions = self.topology.directions(&c);
+            directions.len() == 4
+                && directions.iter().all(|d| {
+                    matches!(
+                        *d,
+                        Direction::North | Direction::East | Direction::South | Direction::West
+                    )
+                })
+        })
+    }
+
+    /// A lower bound of the number of steps between two cells
+    pub fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        self.topology.distance(from, to)
+    }
+}
+
+impl Maze {
+    /// The cells of `path`, to look them up without going through the whole path
+    fn path_cells(&self, path: &[Coord]) -> CellSet {
+        let mut cells = CellSet::new(self);
+        for coord in path {
+            cells.insert(*coord);
+        }
+
+        cells
+    }
+
+    /// Draws the maze as text, marking the start, the end and every cell of `path`. Only mazes of
+    /// square cells can be drawn this way.
+    pub fn to_ascii(&self, path: &[Coord]) -> Result<String> {
+        if !self.has_square_cells() {
+            return Err(Error::UnsupportedShape("Text output".to_string()));
+        }
+
+        let (width, height) = self.topology.size();
+        let (width, height) = (width.ceil() as i32, height.ceil() as i32);
+        let path = self.path_cells(path);
+        // Only the walls next to a cell of the maze are drawn, so masked mazes keep their outline
+        let exists = |x: i32, y: i32| self.contains(&[x, y].into());
+        let corner = |x: i32, y: i32| {
+            exists(x - 1, y - 1) || exists(x, y - 1) || exists(x - 1, y) || exists(x, y)
+        };
+        let mut out = String::new();
+
+        for y in 0..=height {
+            for x in 0..width {
+                out.push(if corner(x, y) { '+' } else { ' ' });
+                let wall = if y < height {
+                    self.north_wall(&[x, y].into())
+                } else {
+                    self.south_wall(&[x, y - 1].into())
+                };
+                let drawn = (exists(x, y - 1) || exists(x, y)) && self.has_wall(&wall);
+                out.push_str(if drawn { "---" } else { "   " });
+            }
+            out.push(if corner(width, y) { '+' } else { ' ' });
+            out.push('\n');
+
+            if y == height {
+                break;
+            }
+
+            for x in 0..width {
+                let coord = [x, y].into();
+                let wall = self.west_wall(&coord);
+                let drawn = (exists(x - 1, y) || exists(x, y)) && self.has_wall(&wall);
+                out.push(if drawn { '|' } else { ' ' });
+                out.push_str(if coord == self.start {
+                    " S "
+                } else if coord == self.end {
+                    " E "
+                } else if path.contains(&coord) {
+                    " . "
+                } else {
+                    "   "
+                });
+            }
+            let wall = self.east_wall(&[width - 1, y].into());
+            let drawn = exists(width - 1, y) && self.has_wall(&wall);
+            out.push(if drawn { '|' } else { ' ' });
+            out.push('\n');
+        }
+
+        let lines: Vec<&str> = out.lines().map(|line| line.trim_end()).collect();
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Draws the maze as an SVG image with `cell_size` pixels per cell, marking the start, the end
+    /// and every cell of `path`. Floors are drawn side by side, from the bottom one.
+    pub fn to_svg(&self, path: &[Coord], cell_size: f32) -> String {
+        let (width, height) = self.topology.size();
+        let depth = self.topology.depth() as f32;
+        // Floors are one cell apart
+        let floor_width = width + 1.0;
+        let path = self.path_cells(path);
+
+        let points = |points: Vec<(f32, f32)>, floor: i32| {
+            let offset = floor as f32 * floor_width;
+            points
+                .iter()
+                .map(|(x, y)| format!("{:.2},{:.2}", (x + offset) * cell_size, y * cell_size))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\">\n",
+            (floor_width * depth - 1.0) * cell_size,
+            height * cell_size
+        );
+        out.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+        for coord in self.coords() {
+            let color = if coord == self.start {
+                "#95c623"
+            } else if coord == self.end {
+                "#e55812"
+            } else if path.contains(&coord) {
+                "#a3bbad"
+            } else {
+                continue;
+            };
+            out.push_str(&format!(
+                "<polygon points=\"{}\" fill=\"{}\"/>\n",
+                points(self.topology.cell_shape(&coord), coord.z),
+                color
+            ));
+        }
+
+        for coord in self.coords() {
+            for direction in self.stairs(&coord) {
+                out.push_str(&format!(
+                    "<polygon points=\"{}\" fill=\"#0e4749\"/>\n",
+                    points(self.topology.stair_shape(&coord, direction), coord.z)
+                ));
+            }
+        }
+
+        for (wall, line) in self.wall_lines() {
+            out.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-linecap=\"round\"/>\n",
+                points(line, wall.coord().z)
+            ));
+        }
+        out.push_str("</svg>\n");
 
-    pub fn maze_width(&self) -> u32 {
-        self.config.maze_width()
+        out
     }
+}
 
-    pub fn maze_height(&self) -> u32 {
-        self.config.maze_height()
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to_ascii(&[]) {
+            Ok(ascii) => write!(f, "{}", ascii),
+            Err(_) => write!(f, "[Maze of {} cells]", self.cell_count()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::{Seed, Stream};
+    use topology::{TopologyType, Wrap};
+
+    #[test]
+    fn walls_are_put_back() {
+        let mut maze = Maze::empty(3, 3);
+        let wall = maze.east_wall(&[0, 0].into());
+        assert!(!maze.has_wall(&wall));
+        assert!(maze.add_wall(&wall));
+        assert!(maze.has_wall(&wall));
+        assert!(!maze.add_wall(&wall));
+
+        // The border is always there
+        let border = maze.west_wall(&[0, 0].into());
+        assert!(!maze.add_wall(&border));
+        assert!(maze.has_wall(&border));
+
+        maze.unlink(&[1, 1].into(), &[1, 2].into()).unwrap();
+        assert!(maze.has_wall(&maze.south_wall(&[1, 1].into())));
+        assert!(maze.unlink(&[0, 0].into(), &[2, 2].into()).is_err());
+    }
+
+    #[test]
+    fn fixed_end_rolls_the_start_again() {
+        for seed in 0..1000 {
+            let mut random = Random::new(Seed(seed), Stream::Placement);
+            let maze = MazeBuilder::new(4, 4)
+                .end([1, 1].into())
+                .build(&mut random)
+                .unwrap();
+            assert_eq!(maze.end, [1, 1].into());
+            assert!(maze.start != maze.end);
+        }
+    }
+
+    #[test]
+    fn fixed_end_on_an_entrance() {
+        let mut layout = Random::new(Seed(1), Stream::Layout);
+        let topology = TopologyType::Polar
+            .init(6, 4, 1, Wrap::None, &mut layout)
+            .unwrap();
+        for seed in 0..100 {
+            let mut random = Random::new(Seed(seed), Stream::Placement);
+            let maze = MazeBuilder::with_topology(topology.clone())
+                .end([0, 0].into())
+                .build(&mut random)
+                .unwrap();
+            assert!(maze.start != maze.end);
+        }
     }
 }
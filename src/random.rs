@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{self, ChaChaRng, Rng, SeedableRng};
+
+use error::{Error, Result};
+
+/// The seed of a maze.
+///
+/// Parsed from a number, or from any other text by hashing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    pub fn random() -> Seed {
+        Seed(rand::thread_rng().next_u64())
+    }
+}
+
+impl FromStr for Seed {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(seed) = s.parse() {
+            return Ok(Seed(seed));
+        }
+
+        // 64 bit FNV-1a, which is stable across versions and platforms
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in s.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+
+        Ok(Seed(hash))
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The independent random streams derived from a single seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// Placement of the start and end of the maze
+    Placement,
+    /// Generation of the maze passages
+    Generation,
+    /// Layout of the cells of irregular mazes
+    Layout,
+}
+
+impl Stream {
+    fn id(self) -> u32 {
+        match self {
+            Stream::Placement => 0,
+            Stream::Generation => 1,
+            Stream::Layout => 2,
+        }
+    }
+}
+
+/// A random number generator that produces the same numbers for the same seed on every platform.
+///
+/// Only `u32` values are drawn from the underlying generator, so nothing depends on the size of
+/// `usize`.
+#[derive(Debug, Clone)]
+pub struct Random {
+    rng: ChaChaRng,
+}
+
+impl Random {
+    pub fn new(seed: Seed, stream: Stream) -> Random {
+        let key = [seed.0 as u32, (seed.0 >> 32) as u32, stream.id()];
+
+        Random {
+            rng: ChaChaRng::from_seed(&key[..]),
+        }
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.rng.next_u32() & 1 == 1
+    }
+
+    /// A number in `[low, high)`
+    pub fn range(&mut self, low: u32, high: u32) -> u32 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        let span = high - low;
+        // Reject the top values that would make some results more likely than others
+        let limit = u32::MAX - u32::MAX % span;
+
+        loop {
+            let value = self.rng.next_u32();
+            if value < limit {
+                return low + value % span;
+            }
+        }
+    }
+
+    /// A number in `[0, 1)`, with the 24 bits of precision an `f32` has
+    pub fn unit(&mut self) -> f32 {
+        (self.rng.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// An index into a slice of `len` elements
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len <= u32::MAX as usize);
+        self.range(0, len as u32) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.index(i + 1);
+            values.swap(i, j);
+        }
+    }
+
+    pub fn choose<'a, T>(&mut self, values: &'a [T]) -> Option<&'a T> {
+        if values.is_empty() {
+            None
+        } else {
+            Some(&values[self.index(values.len())])
+        }
+    }
+}
+
+impl Rng for Random {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_seeds() {
+        assert_eq!("42".parse::<Seed>().unwrap(), Seed(42));
+        assert_eq!(
+            "18446744073709551615".parse::<Seed>().unwrap(),
+            Seed(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn text_is_hashed() {
+        assert_eq!(
+            "hello".parse::<Seed>().unwrap(),
+            Seed(11_831_194_018_420_276_491)
+        );
+        assert_eq!("".parse::<Seed>().unwrap(), Seed(0xcbf2_9ce4_8422_2325));
+        assert!("-1".parse::<Seed>().unwrap() != Seed(1));
+    }
+
+    #[test]
+    fn streams_are_stable_and_independent() {
+        let draw = |stream| {
+            let mut random = Random::new(Seed(42), stream);
+            (0..5).map(|_| random.range(0, 100)).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(Stream::Generation), vec![78, 65, 56, 56, 31]);
+        assert_eq!(draw(Stream::Placement), vec![91, 9, 26, 41, 26]);
+    }
+}
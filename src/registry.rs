@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use error::{Error, Result};
+use generator::{Generator, GeneratorType};
+use maze::Maze;
+use random::Random;
+use solver::{Solver, SolverType};
+use zoned::{ZoneLayout, Zoned};
+
+/// A parameter accepted by an algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: &'static str,
+}
+
+/// Parameter values, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Params {
+        Params::default()
+    }
+
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    pub fn raw(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|v| v.as_ref())
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T> {
+        let value = self
+            .raw(name)
+            .ok_or_else(|| Error::MissingParam(name.to_string()))?;
+
+        value
+            .parse()
+            .map_err(|_| Error::InvalidParam(name.to_string(), value.to_string()))
+    }
+
+    /// Checks every value against `schema` and fills in the defaults of the missing ones
+    fn resolve(&self, schema: &[Param]) -> Result<Params> {
+        if let Some(name) = self
+            .values
+            .keys()
+            .find(|name| schema.iter().all(|p| p.name != name.as_str()))
+        {
+            return Err(Error::UnknownParam(name.clone()));
+        }
+
+        let mut resolved = self.clone();
+        for param in schema {
+            if !resolved.values.contains_key(param.name) {
+                resolved.set(param.name, param.default);
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+type GeneratorInit =
+    dyn Fn(&Maze, &mut Random, &Params) -> Result<Box<dyn Generator>> + Send + Sync;
+type SolverInit = dyn Fn(&Maze, &Params) -> Result<Box<dyn Solver>> + Send + Sync;
+
+pub struct GeneratorEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: Vec<Param>,
+    init: Box<GeneratorInit>,
+}
+
+impl GeneratorEntry {
+    pub fn init(
+        &self,
+        maze: &Maze,
+        random: &mut Random,
+        params: &Params,
+    ) -> Result<Box<dyn Generator>> {
+        (self.init)(maze, random, &params.resolve(&self.params)?)
+    }
+}
+
+impl fmt::Debug for GeneratorEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GeneratorEntry")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+pub struct SolverEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: Vec<Param>,
+    init: Box<SolverInit>,
+}
+
+impl SolverEntry {
+    pub fn init(&self, maze: &Maze, params: &Params) -> Result<Box<dyn Solver>> {
+        (self.init)(maze, &params.resolve(&self.params)?)
+    }
+}
+
+impl fmt::Debug for SolverEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolverEntry")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+/// The generators and solvers available by name.
+///
+/// `Registry::default()` contains every algorithm of this crate. Other algorithms can be added
+/// with `register_generator` and `register_solver`, replacing any existing one with the same
+/// name. Names are looked up ignoring case.
+#[derive(Debug)]
+pub struct Registry {
+    generators: Vec<GeneratorEntry>,
+    solvers: Vec<SolverEntry>,
+}
+
+impl Registry {
+    /// A registry without any algorithms
+    pub fn new() -> Registry {
+        Registry {
+            generators: vec![],
+            solvers: vec![],
+        }
+    }
+
+    pub fn register_generator<F>(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        params: Vec<Param>,
+        init: F,
+    ) where
+        F: Fn(&Maze, &mut Random, &Params) -> Result<Box<dyn Generator>> + Send + Sync + 'static,
+    {
+        self.generators
+            .retain(|g| !g.name.eq_ignore_ascii_case(name));
+        self.generators.push(GeneratorEntry {
+            name,
+            description,
+            params,
+            init: Box::new(init),
+        });
+    }
+
+    pub fn register_solver<F>(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        params: Vec<Param>,
+        init: F,
+    ) where
+        F: Fn(&Maze, &Params) -> Result<Box<dyn Solver>> + Send + Sync + 'static,
+    {
+        self.solvers.retain(|s| !s.name.eq_ignore_ascii_case(name));
+        self.solvers.push(SolverEntry {
+            name,
+            description,
+            params,
+            init: Box::new(init),
+        });
+    }
+
+    pub fn generators(&self) -> &[GeneratorEntry] {
+        &self.generators
+    }
+
+    pub fn solvers(&self) -> &[SolverEntry] {
+        &self.solvers
+    }
+
+    pub fn generator(&self, name: &str) -> Result<&GeneratorEntry> {
+        self.generators
+            .iter()
+            .find(|g| g.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::UnsupportedGenerator(name.to_string()))
+    }
+
+    pub fn solver(&self, name: &str) -> Result<&SolverEntry> {
+        self.solvers
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::UnsupportedSolver(name.to_string()))
+    }
+
+    pub fn generator_names(&self) -> Vec<&'static str> {
+        self.generators.iter().map(|g| g.name).collect()
+    }
+
+    pub fn solver_names(&self) -> Vec<&'static str> {
+        self.solvers.iter().map(|s| s.name).collect()
+    }
+
+    /// A description of every generator and its parameters, one per line
+    pub fn generators_help(&self) -> String {
+        self.generators
+            .iter()
+            .map(|g| help_line(g.name, g.description, &g.params))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// A description of every solver and its parameters, one per line
+    pub fn solvers_help(&self) -> String {
+        self.solvers
+            .iter()
+            .map(|s| help_line(s.name, s.description, &s.params))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn zoned(maze: &Maze, random: &mut Random, params: &Params) -> Result<Box<dyn Generator>> {
+    let layout = match params.raw("map") {
+        Some(path) if !path.is_empty() => {
+            let text = fs::read_to_string(path)
+                .map_err(|_| Error::InvalidParam("map".to_string(), path.to_string()))?;
+            ZoneLayout::from_text(&text)
+        }
+        _ => params.get("zones")?,
+    };
+    let generators = params
+        .raw("generators")
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().parse())
+        .collect::<Result<Vec<GeneratorType>>>()?;
+
+    Ok(Box::new(Zoned::new(
+        maze,
+        random,
+        &layout,
+        &generators,
+        params.get("doors")?,
+    )?))
+}
+
+fn help_line(name: &str, description: &str, params: &[Param]) -> String {
+    let mut line = format!("{}: {}", name, description);
+    for param in params {
+        line.push_str(&format!(
+            "\n    {}={} ({})",
+            param.name, param.default, param.description
+        ));
+    }
+
+    line
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        let mut registry = Registry::new();
+
+        for &generator in &GeneratorType::all() {
+            registry.register_generator(
+                generator.name(),
+                generator.description(),
+                generator.params(),
+                move |maze, random, params| generator.init_with(maze, random, params),
+            );
+        }
+
+        registry.register_generator(
+            "zoned",
+            "Zones generated by different generators, joined by a few doors",
+            vec![
+                Param {
+                    name: "zones",
+                    description: "quadrants, halves or <columns>x<rows> rectangles",
+                    default: "quadrants",
+                },
+                Param {
+                    name: "map",
+                    description: "a text file with a character per cell, one per zone, \
+                                  used instead of zones",
+                    default: "",
+                },
+                Param {
+                    name: "generators",
+                    description: "the generators of the zones, separated by commas",
+                    default: "eller,dfs",
+                },
+                Param {
+                    name: "doors",
+                    description: "the number of doors between two neighbouring zones",
+                    default: "1",
+                },
+            ],
+            zoned,
+        );
+
+        for &solver in &SolverType::all() {
+            registry.register_solver(
+                solver.name(),
+                solver.description(),
+                vec![],
+                move |maze, _| Ok(solver.init(maze)),
+            );
+        }
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_ignore_case() {
+        let mut registry = Registry::new();
+        registry.register_generator("Kruskal", "", vec![], |maze, random, _| {
+            GeneratorType::Kruskal.init(maze, random)
+        });
+        registry.register_solver("BFS", "", vec![], |maze, _| Ok(SolverType::BFS.init(maze)));
+
+        assert_eq!(registry.generator("kruskal").unwrap().name, "Kruskal");
+        assert_eq!(registry.generator("KRUSKAL").unwrap().name, "Kruskal");
+        assert_eq!(registry.solver("bfs").unwrap().name, "BFS");
+
+        // Registering a name again replaces it, whatever its case
+        registry.register_solver("bfs", "", vec![], |maze, _| Ok(SolverType::BFS.init(maze)));
+        assert_eq!(registry.solver_names(), vec!["bfs"]);
+    }
+}
@@ -0,0 +1,263 @@
+//! Rendering of mazes with ggez. Only available with the `visualizer` feature.
+
+use std::collections::HashSet;
+
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, MeshBuilder};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+use event::{Event, Observer};
+use maze::{Coord, Maze};
+
+pub const COLOR_BACKGROUND: (u8, u8, u8) = (7, 16, 19);
+pub const COLOR_START: (u8, u8, u8) = (149, 198, 35);
+pub const COLOR_END: (u8, u8, u8) = (229, 88, 18);
+
+pub const COLOR_WALL: (u8, u8, u8) = (239, 231, 218);
+pub const COLOR_STAIRS: (u8, u8, u8) = (239, 231, 218);
+
+pub const COLOR_EXPLORED: (u8, u8, u8) = (14, 71, 73);
+pub const COLOR_HIGHLIGHT_BRIGHT: (u8, u8, u8) = (163, 187, 173);
+pub const COLOR_HIGHLIGHT_MEDIUM: (u8, u8, u8) = (53, 114, 102);
+pub const COLOR_HIGHLIGHT_DARK: (u8, u8, u8) = (57, 104, 106);
+
+pub const CELL_WALL_WIDTH: f32 = 1.0;
+
+/// Keeps track of what generators and solvers are doing, so it can be drawn on top of the maze
+#[derive(Debug, Default, Clone)]
+pub struct Visualization {
+    pub explored: HashSet<Coord>,
+    pub highlight_bright: HashSet<Coord>,
+    pub highlight_medium: HashSet<Coord>,
+    pub highlight_dark: HashSet<Coord>,
+}
+
+impl Visualization {
+    pub fn clear(&mut self) {
+        self.explored.clear();
+        self.highlight_bright.clear();
+        self.highlight_medium.clear();
+        self.highlight_dark.clear();
+    }
+}
+
+impl Observer for Visualization {
+    fn notify(&mut self, event: Event) {
+        match event {
+            Event::Current(coords) => self.highlight_bright = coords.into_iter().collect(),
+            Event::CellVisited(coord) => {
+                self.explored.insert(coord);
+            }
+            Event::PassageCarved(_, _) | Event::WallAdded(_, _) => {}
+            Event::FrontierAdded(coord) => {
+                self.highlight_dark.insert(coord);
+            }
+            Event::FrontierRemoved(coord) => {
+                self.highlight_dark.remove(&coord);
+            }
+            Event::PathPushed(coord) => {
+                self.highlight_medium.insert(coord);
+            }
+            Event::Backtracked(coord) => {
+                self.highlight_medium.remove(&coord);
+            }
+            Event::PathUpdated(path) => self.highlight_medium = path.into_iter().collect(),
+            Event::Scanning(coords) => self.highlight_dark = coords.into_iter().collect(),
+        }
+    }
+}
+
+fn scale(points: Vec<(f32, f32)>, cell_width: f32, cell_height: f32) -> Vec<Point2<f32>> {
+    points
+        .into_iter()
+        .map(|(x, y)| Point2 {
+            x: x * cell_width,
+            y: y * cell_height,
+        })
+        .collect()
+}
+
+fn build_wall_mesh(
+    line: Vec<(f32, f32)>,
+    mb: &mut MeshBuilder,
+    color: Color,
+    cell_width: f32,
+    cell_height: f32,
+) {
+    let points = scale(line, cell_width, cell_height);
+
+    mb.line(&points, CELL_WALL_WIDTH, color).unwrap();
+}
+
+fn build_cell_mesh(
+    maze: &Maze,
+    coord: &Coord,
+    mb: &mut MeshBuilder,
+    color: Color,
+    cell_width: f32,
+    cell_height: f32,
+) {
+    let points = scale(maze.topology().cell_shape(coord), cell_width, cell_height);
+
+    mb.polygon(DrawMode::fill(), &points, color).unwrap();
+}
+
+fn build_stairs_mesh(
+    maze: &Maze,
+    coord: &Coord,
+    mb: &mut MeshBuilder,
+    color: Color,
+    cell_width: f32,
+    cell_height: f32,
+) {
+    for direction in maze.stairs(coord) {
+        let shape = maze.topology().stair_shape(coord, direction);
+        if shape.len() < 3 {
+            continue;
+        }
+        let points = scale(shape, cell_width, cell_height);
+        mb.polygon(DrawMode::fill(), &points, color).unwrap();
+    }
+}
+
+impl Maze {
+    /// Draws a single floor of the maze
+    pub fn render(
+        &self,
+        ctx: &mut Context,
+        visualization: &Visualization,
+        cell_width: u32,
+        cell_height: u32,
+        floor: i32,
+    ) -> GameResult<()> {
+        let (cell_width, cell_height) = (cell_width as f32, cell_height as f32);
+
+        let mut start_mb = MeshBuilder::new();
+        let mut end_mb = MeshBuilder::new();
+        let mut highlight_bright_mb = MeshBuilder::new();
+        let mut highlight_medium_mb = MeshBuilder::new();
+        let mut highlight_dark_mb = MeshBuilder::new();
+        let mut explored_mb = MeshBuilder::new();
+        let mut stairs_mb = MeshBuilder::new();
+
+        for coord in self.coords().filter(|c| c.z == floor) {
+            build_stairs_mesh(
+                self,
+                &coord,
+                &mut stairs_mb,
+                COLOR_STAIRS.into(),
+                cell_width,
+                cell_height,
+            );
+
+            if coord == self.start {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut start_mb,
+                    COLOR_START.into(),
+                    cell_width,
+                    cell_height,
+                );
+            } else if coord == self.end {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut end_mb,
+                    COLOR_END.into(),
+                    cell_width,
+                    cell_height,
+                );
+            } else if visualization.highlight_bright.contains(&coord) {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut highlight_bright_mb,
+                    COLOR_HIGHLIGHT_BRIGHT.into(),
+                    cell_width,
+                    cell_height,
+                );
+            } else if visualization.highlight_medium.contains(&coord) {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut highlight_medium_mb,
+                    COLOR_HIGHLIGHT_MEDIUM.into(),
+                    cell_width,
+                    cell_height,
+                );
+            } else if visualization.highlight_dark.contains(&coord) {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut highlight_dark_mb,
+                    COLOR_HIGHLIGHT_DARK.into(),
+                    cell_width,
+                    cell_height,
+                );
+            } else if visualization.explored.contains(&coord) {
+                build_cell_mesh(
+                    self,
+                    &coord,
+                    &mut explored_mb,
+                    COLOR_EXPLORED.into(),
+                    cell_width,
+                    cell_height,
+                );
+            }
+        }
+
+        if let Ok(m) = start_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = end_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = highlight_bright_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = highlight_medium_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = highlight_dark_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = explored_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+        if let Ok(m) = stairs_mb.build(ctx) {
+            graphics::draw(ctx, &m, graphics::DrawParam::default())?;
+        }
+
+        // Use a list of mesh builder because we might run into vertex buffer limits
+        let mut walls_mbs = self
+            .wall_lines()
+            .into_iter()
+            .filter(|(wall, _)| wall.coord().z == floor)
+            .map(|(_, line)| line)
+            .collect::<Vec<_>>()
+            .chunks(10_000)
+            .map(|lines| {
+                let mut mb = MeshBuilder::new();
+                for line in lines {
+                    build_wall_mesh(
+                        line.clone(),
+                        &mut mb,
+                        COLOR_WALL.into(),
+                        cell_width,
+                        cell_height,
+                    );
+                }
+                mb
+            })
+            .collect::<Vec<_>>();
+
+        for mb in &mut walls_mbs {
+            let mesh = mb.build(ctx)?;
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,16 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
 
+use bitset::CellSet;
+use budget::Budget;
 use error::{Error, Result};
+use event::{Event, Observer};
 use maze::{Coord, Direction, Maze};
+use topology::Topology;
 
 #[derive(Debug, Clone, Copy)]
 pub enum SolverType {
@@ -15,9 +22,34 @@ pub enum SolverType {
 }
 
 impl SolverType {
-    /// A list of possible variants in `&'static str` form
-    pub fn variants() -> [&'static str; 5] {
-        ["dfs", "bfs", "dijkstra", "greedy", "astar"]
+    pub fn all() -> [SolverType; 5] {
+        [
+            SolverType::DFS,
+            SolverType::BFS,
+            SolverType::Dijkstra,
+            SolverType::Greedy,
+            SolverType::AStar,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SolverType::DFS => "dfs",
+            SolverType::BFS => "bfs",
+            SolverType::Dijkstra => "dijkstra",
+            SolverType::Greedy => "greedy",
+            SolverType::AStar => "astar",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match *self {
+            SolverType::DFS => "Depth first search, follows one path until a dead end",
+            SolverType::BFS => "Breadth first search, finds the shortest path",
+            SolverType::Dijkstra => "Dijkstra, finds the shortest path by distance from the start",
+            SolverType::Greedy => "Greedy best first search, heads straight for the end",
+            SolverType::AStar => "A*, shortest path guided by the distance to the end",
+        }
     }
 
     pub fn init(&self, maze: &Maze) -> Box<dyn Solver> {
@@ -35,34 +67,217 @@ impl FromStr for SolverType {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_ref() {
-            "dfs" => Ok(SolverType::DFS),
-            "bfs" => Ok(SolverType::BFS),
-            "dijkstra" => Ok(SolverType::Dijkstra),
-            "greedy" => Ok(SolverType::Greedy),
-            "astar" => Ok(SolverType::AStar),
-            _ => Err(Error::UnsupportedSolver(s.to_string())),
-        }
+        let lowercase = s.to_lowercase();
+        SolverType::all()
+            .iter()
+            .find(|s| s.name() == lowercase)
+            .cloned()
+            .ok_or_else(|| Error::UnsupportedSolver(s.to_string()))
     }
 }
 
 pub trait Solver {
     fn is_done(&self) -> bool;
-    fn tick(&mut self, maze: &mut Maze) -> Result<()>;
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()>;
+
+    /// The path from the start to the cell currently being looked at. Once done, this is the
+    /// solution of the maze.
+    fn path(&self) -> Vec<Coord>;
+
+    /// Ticks until the maze is solved or the budget runs out, returning whether it's done
+    fn run_for(
+        &mut self,
+        maze: &Maze,
+        observer: &mut dyn Observer,
+        budget: Budget,
+    ) -> Result<bool> {
+        let started = Instant::now();
+        let mut ticks = 0;
+
+        while !self.is_done() && !budget.exhausted(ticks, started) {
+            self.tick(maze, observer)?;
+            ticks += 1;
+        }
+
+        Ok(self.is_done())
+    }
+
+    /// Ticks until the maze is solved, returning the solution
+    fn solve(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<Solution> {
+        let mut counter = ExploredCounter {
+            observer,
+            explored: 0,
+        };
+        self.run_for(maze, &mut counter, Budget::Unlimited)?;
+
+        Ok(Solution {
+            path: self.path(),
+            explored: counter.explored,
+        })
+    }
+}
+
+/// Counts the visited cells on their way to another observer
+struct ExploredCounter<'a> {
+    observer: &'a mut dyn Observer,
+    explored: usize,
+}
+
+impl<'a> Observer for ExploredCounter<'a> {
+    fn notify(&mut self, event: Event) {
+        if let Event::CellVisited(_) = event {
+            self.explored += 1;
+        }
+        self.observer.notify(event);
+    }
+
+    fn is_listening(&self) -> bool {
+        self.observer.is_listening()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// Every cell from the start to the end of the maze
+    pub path: Vec<Coord>,
+    /// How many cells were visited while looking for the path
+    pub explored: usize,
+}
+
+impl Solution {
+    /// The number of steps from the start to the end of the maze
+    pub fn len(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Solves a generated maze using the given algorithm
+pub fn solve(maze: &Maze, solver: SolverType) -> Result<Solution> {
+    solve_with(maze, solver, &mut ())
+}
+
+/// Same as `solve`, reporting every step to `observer`
+pub fn solve_with(
+    maze: &Maze,
+    solver: SolverType,
+    observer: &mut dyn Observer,
+) -> Result<Solution> {
+    solver.init(maze).solve(maze, observer)
+}
+
+/// A cell waiting in a `Frontier`
+struct Queued<T> {
+    priority: u32,
+    /// When the cell was queued, so that cells with the same priority come out in order
+    order: usize,
+    coord: Coord,
+    node: T,
+}
+
+impl<T> PartialEq for Queued<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.priority, self.order) == (other.priority, other.order)
+    }
+}
+
+impl<T> Eq for Queued<T> {}
+
+impl<T> PartialOrd for Queued<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Queued<T> {
+    // `BinaryHeap` pops the greatest element, so the lowest priority has to compare greatest
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.priority, other.order).cmp(&(self.priority, self.order))
+    }
+}
+
+/// The cells a solver still has to look at, lowest priority first. A cell queued again with a
+/// lower priority is moved ahead, so solvers can find shorter ways to it while it waits.
+struct Frontier<T> {
+    heap: BinaryHeap<Queued<T>>,
+    topology: Arc<dyn Topology>,
+    /// The priority of every queued cell, by index
+    priorities: Vec<Option<u32>>,
+    queued: usize,
+}
+
+impl<T> Frontier<T> {
+    fn new(maze: &Maze) -> Frontier<T> {
+        Frontier {
+            heap: BinaryHeap::new(),
+            topology: maze.topology().clone(),
+            priorities: vec![None; maze.cell_count()],
+            queued: 0,
+        }
+    }
+
+    fn index(&self, coord: &Coord) -> usize {
+        self.topology
+            .index(coord)
+            .expect("Only cells of the maze can be queued")
+    }
+
+    fn contains(&self, coord: &Coord) -> bool {
+        self.priorities[self.index(coord)].is_some()
+    }
+
+    /// Queues a cell, unless it's already queued with the same or a lower priority
+    fn push(&mut self, coord: Coord, priority: u32, node: T) {
+        let idx = self.index(&coord);
+        if self.priorities[idx].is_some_and(|queued| queued <= priority) {
+            return;
+        }
+
+        self.priorities[idx] = Some(priority);
+        self.heap.push(Queued {
+            priority,
+            order: self.queued,
+            coord,
+            node,
+        });
+        self.queued += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        while let Some(queued) = self.heap.pop() {
+            // Cells that were queued again with a lower priority leave their old entry behind
+            let idx = self.index(&queued.coord);
+            if self.priorities[idx] == Some(queued.priority) {
+                self.priorities[idx] = None;
+                return Some(queued.node);
+            }
+        }
+
+        None
+    }
 }
 
 pub struct DFS {
     current: Coord,
     goal: Coord,
     stack: Vec<Coord>,
+    explored: CellSet,
 }
 
 impl DFS {
-    fn new(maze: &Maze) -> DFS {
+    pub fn new(maze: &Maze) -> DFS {
+        // Every cell on the stack is explored, so no cell is pushed twice
+        let mut explored = CellSet::new(maze);
+        explored.insert(maze.start);
+
         DFS {
             current: maze.start,
             goal: maze.end,
             stack: vec![],
+            explored,
         }
     }
 
@@ -73,8 +288,7 @@ impl DFS {
 
         maze.connected_neighbours(&self.current)
             .into_iter()
-            .filter(|(c, _)| !maze.explored.contains(&c))
-            .find(|(c, _)| !self.stack.contains(&c))
+            .find(|(c, _)| !self.explored.contains(c))
     }
 }
 
@@ -83,25 +297,30 @@ impl Solver for DFS {
         self.goal == self.current
     }
 
-    fn tick(&mut self, maze: &mut Maze) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn path(&self) -> Vec<Coord> {
+        let mut path = self.stack.clone();
+        path.push(self.current);
 
-        match self.available_neighbour(&maze) {
-            Some((neighbour, _)) => {
-                maze.explored.insert(neighbour);
+        path
+    }
 
-                maze.highlight_medium.insert(neighbour);
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()> {
+        match self.available_neighbour(maze) {
+            Some((neighbour, _)) => {
+                self.explored.insert(neighbour);
+                observer.notify(Event::CellVisited(neighbour));
+                observer.notify(Event::PathPushed(neighbour));
 
                 self.stack.push(self.current);
                 self.current = neighbour;
             }
             None => {
-                maze.highlight_medium.remove(&self.current);
+                observer.notify(Event::Backtracked(self.current));
                 self.current = self.stack.pop().ok_or(Error::ImpossibleMaze)?;
             }
         }
 
-        maze.highlight_bright.insert(self.current);
+        observer.notify(Event::Current(vec![self.current]));
 
         Ok(())
     }
@@ -116,10 +335,12 @@ pub struct BFS {
     current: Rc<BFSNode>,
     goal: Coord,
     queue: VecDeque<Rc<BFSNode>>,
+    in_queue: CellSet,
+    explored: CellSet,
 }
 
 impl BFS {
-    fn new(maze: &Maze) -> BFS {
+    pub fn new(maze: &Maze) -> BFS {
         BFS {
             current: Rc::new(BFSNode {
                 coord: maze.start,
@@ -127,6 +348,8 @@ impl BFS {
             }),
             goal: maze.end,
             queue: VecDeque::new(),
+            in_queue: CellSet::new(maze),
+            explored: CellSet::new(maze),
         }
     }
 
@@ -137,47 +360,51 @@ impl BFS {
 
         maze.connected_neighbours(&self.current.coord)
             .into_iter()
-            .filter(|(c, _)| !maze.explored.contains(&c))
-            .filter(|(c, _)| self.queue.iter().find(|hc| hc.coord == *c).is_none())
+            .filter(|(c, _)| !self.explored.contains(c))
+            .filter(|(c, _)| !self.in_queue.contains(c))
             .collect()
     }
-
-    fn highlight_path_from_cell(hc: &BFSNode, maze: &mut Maze) {
-        maze.highlight_medium.insert(hc.coord);
-        if let Some(ref previous) = hc.previous {
-            BFS::highlight_path_from_cell(&previous, maze);
-        }
-    }
-
-    fn highlight_path(&self, maze: &mut Maze) {
-        maze.highlight_medium.clear();
-        BFS::highlight_path_from_cell(&self.current, maze);
-    }
 }
 
-impl<'a> Solver for BFS {
+impl Solver for BFS {
     fn is_done(&self) -> bool {
         self.goal == self.current.coord
     }
 
-    fn tick(&mut self, maze: &mut Maze) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn path(&self) -> Vec<Coord> {
+        let mut path = vec![];
+        let mut node = Some(&self.current);
+        while let Some(n) = node {
+            path.push(n.coord);
+            node = n.previous.as_ref();
+        }
+        path.reverse();
+
+        path
+    }
 
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()> {
         for (neighbour, _) in self.available_neighbours(maze) {
             self.queue.push_back(Rc::new(BFSNode {
                 coord: neighbour,
                 previous: Some(self.current.clone()),
             }));
-            maze.highlight_dark.insert(neighbour);
+            self.in_queue.insert(neighbour);
+            observer.notify(Event::FrontierAdded(neighbour));
         }
 
         self.current = self.queue.pop_front().ok_or(Error::ImpossibleMaze)?;
-        maze.highlight_dark.remove(&self.current.coord);
+        self.in_queue.remove(&self.current.coord);
+        observer.notify(Event::FrontierRemoved(self.current.coord));
 
-        maze.explored.insert(self.current.coord);
+        if self.explored.insert(self.current.coord) {
+            observer.notify(Event::CellVisited(self.current.coord));
+        }
 
-        maze.highlight_bright.insert(self.current.coord);
-        self.highlight_path(maze);
+        observer.notify(Event::Current(vec![self.current.coord]));
+        if observer.is_listening() {
+            observer.notify(Event::PathUpdated(self.path()));
+        }
 
         Ok(())
     }
@@ -190,15 +417,15 @@ struct DijkstraNode {
     previous: Option<Rc<DijkstraNode>>,
 }
 
-#[derive(Debug)]
 pub struct Dijkstra {
     current: Rc<DijkstraNode>,
     goal: Coord,
-    queue: Vec<DijkstraNode>,
+    queue: Frontier<Rc<DijkstraNode>>,
+    explored: CellSet,
 }
 
 impl Dijkstra {
-    fn new(maze: &Maze) -> Dijkstra {
+    pub fn new(maze: &Maze) -> Dijkstra {
         Dijkstra {
             current: Rc::new(DijkstraNode {
                 coord: maze.start,
@@ -206,7 +433,8 @@ impl Dijkstra {
                 previous: None,
             }),
             goal: maze.end,
-            queue: vec![],
+            queue: Frontier::new(maze),
+            explored: CellSet::new(maze),
         }
     }
 
@@ -217,22 +445,9 @@ impl Dijkstra {
 
         maze.connected_neighbours(&self.current.coord)
             .into_iter()
-            .filter(|(c, _)| !maze.explored.contains(&c))
-            .filter(|(c, _)| self.queue.iter().find(|hc| hc.coord == *c).is_none())
+            .filter(|(c, _)| !self.explored.contains(c))
             .collect()
     }
-
-    fn highlight_path_from_cell(hc: &DijkstraNode, maze: &mut Maze) {
-        maze.highlight_medium.insert(hc.coord);
-        if let Some(ref previous) = hc.previous {
-            Dijkstra::highlight_path_from_cell(&previous, maze);
-        }
-    }
-
-    fn highlight_path(&self, maze: &mut Maze) {
-        maze.highlight_medium.clear();
-        Dijkstra::highlight_path_from_cell(&self.current, maze);
-    }
 }
 
 impl Solver for Dijkstra {
@@ -240,10 +455,22 @@ impl Solver for Dijkstra {
         self.goal == self.current.coord
     }
 
-    fn tick(&mut self, maze: &mut Maze) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn path(&self) -> Vec<Coord> {
+        let mut path = vec![];
+        let mut node = Some(&self.current);
+        while let Some(n) = node {
+            path.push(n.coord);
+            node = n.previous.as_ref();
+        }
+        path.reverse();
 
-        maze.explored.insert(self.current.coord);
+        path
+    }
+
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()> {
+        if self.explored.insert(self.current.coord) {
+            observer.notify(Event::CellVisited(self.current.coord));
+        }
         for (neighbour, _) in self.available_neighbours(maze) {
             let dist_to_neighbour = self.current.dist + 1;
 
@@ -253,25 +480,20 @@ impl Solver for Dijkstra {
                 previous: Some(self.current.clone()),
             };
 
-            maze.highlight_dark.insert(neighbour);
-            match self
-                .queue
-                .binary_search_by_key(&new_neighbour.dist, |n| n.dist)
-            {
-                Ok(pos) | Err(pos) => {
-                    self.queue.insert(pos, new_neighbour);
-                }
+            if !self.queue.contains(&neighbour) {
+                observer.notify(Event::FrontierAdded(neighbour));
             }
+            self.queue
+                .push(neighbour, new_neighbour.dist, Rc::new(new_neighbour));
         }
 
-        if self.queue.is_empty() {
-            return Err(Error::ImpossibleMaze);
-        }
-        self.current = Rc::new(self.queue.remove(0));
-        maze.highlight_dark.remove(&self.current.coord);
+        self.current = self.queue.pop().ok_or(Error::ImpossibleMaze)?;
+        observer.notify(Event::FrontierRemoved(self.current.coord));
 
-        maze.highlight_bright.insert(self.current.coord);
-        self.highlight_path(maze);
+        observer.notify(Event::Current(vec![self.current.coord]));
+        if observer.is_listening() {
+            observer.notify(Event::PathUpdated(self.path()));
+        }
 
         Ok(())
     }
@@ -284,15 +506,15 @@ struct GreedyNode {
     previous: Option<Rc<GreedyNode>>,
 }
 
-#[derive(Debug)]
 pub struct Greedy {
     current: Rc<GreedyNode>,
     goal: Coord,
-    queue: Vec<Rc<GreedyNode>>,
+    queue: Frontier<Rc<GreedyNode>>,
+    explored: CellSet,
 }
 
 impl Greedy {
-    fn new(maze: &Maze) -> Greedy {
+    pub fn new(maze: &Maze) -> Greedy {
         Greedy {
             current: Rc::new(GreedyNode {
                 coord: maze.start,
@@ -300,12 +522,13 @@ impl Greedy {
                 previous: None,
             }),
             goal: maze.end,
-            queue: vec![],
+            queue: Frontier::new(maze),
+            explored: CellSet::new(maze),
         }
     }
 
-    fn heuristic(&self, coord: &Coord) -> u32 {
-        self.goal.manhattan_dist(coord)
+    fn heuristic(&self, maze: &Maze, coord: &Coord) -> u32 {
+        maze.distance(&self.goal, coord)
     }
 
     fn available_neighbours(&self, maze: &Maze) -> Vec<(Coord, Direction)> {
@@ -315,22 +538,9 @@ impl Greedy {
 
         maze.connected_neighbours(&self.current.coord)
             .into_iter()
-            .filter(|(c, _)| !maze.explored.contains(&c))
-            .filter(|(c, _)| self.queue.iter().find(|hc| hc.coord == *c).is_none())
+            .filter(|(c, _)| !self.explored.contains(c))
             .collect()
     }
-
-    fn highlight_path_from_cell(hc: &GreedyNode, maze: &mut Maze) {
-        maze.highlight_medium.insert(hc.coord);
-        if let Some(ref previous) = hc.previous {
-            Greedy::highlight_path_from_cell(&previous, maze);
-        }
-    }
-
-    fn highlight_path(&self, maze: &mut Maze) {
-        maze.highlight_medium.clear();
-        Greedy::highlight_path_from_cell(&self.current, maze);
-    }
 }
 
 impl Solver for Greedy {
@@ -338,36 +548,43 @@ impl Solver for Greedy {
         self.goal == self.current.coord
     }
 
-    fn tick(&mut self, maze: &mut Maze) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn path(&self) -> Vec<Coord> {
+        let mut path = vec![];
+        let mut node = Some(&self.current);
+        while let Some(n) = node {
+            path.push(n.coord);
+            node = n.previous.as_ref();
+        }
+        path.reverse();
 
-        maze.explored.insert(self.current.coord);
+        path
+    }
+
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()> {
+        if self.explored.insert(self.current.coord) {
+            observer.notify(Event::CellVisited(self.current.coord));
+        }
         for (neighbour, _) in self.available_neighbours(maze) {
             let new_neighbour = GreedyNode {
                 coord: neighbour,
-                score: self.heuristic(&neighbour),
+                score: self.heuristic(maze, &neighbour),
                 previous: Some(self.current.clone()),
             };
 
-            maze.highlight_dark.insert(neighbour);
-            match self
-                .queue
-                .binary_search_by_key(&new_neighbour.score, |n| n.score)
-            {
-                Ok(pos) | Err(pos) => {
-                    self.queue.insert(pos, Rc::new(new_neighbour));
-                }
+            if !self.queue.contains(&neighbour) {
+                observer.notify(Event::FrontierAdded(neighbour));
             }
+            self.queue
+                .push(neighbour, new_neighbour.score, Rc::new(new_neighbour));
         }
 
-        if self.queue.is_empty() {
-            return Err(Error::ImpossibleMaze);
-        }
-        self.current = self.queue.remove(0);
-        maze.highlight_dark.remove(&self.current.coord);
+        self.current = self.queue.pop().ok_or(Error::ImpossibleMaze)?;
+        observer.notify(Event::FrontierRemoved(self.current.coord));
 
-        maze.highlight_bright.insert(self.current.coord);
-        self.highlight_path(maze);
+        observer.notify(Event::Current(vec![self.current.coord]));
+        if observer.is_listening() {
+            observer.notify(Event::PathUpdated(self.path()));
+        }
 
         Ok(())
     }
@@ -381,15 +598,15 @@ struct AStarNode {
     previous: Option<Rc<AStarNode>>,
 }
 
-#[derive(Debug)]
 pub struct AStar {
     current: Rc<AStarNode>,
     goal: Coord,
-    queue: Vec<Rc<AStarNode>>,
+    queue: Frontier<Rc<AStarNode>>,
+    explored: CellSet,
 }
 
 impl AStar {
-    fn new(maze: &Maze) -> AStar {
+    pub fn new(maze: &Maze) -> AStar {
         AStar {
             current: Rc::new(AStarNode {
                 coord: maze.start,
@@ -398,12 +615,13 @@ impl AStar {
                 previous: None,
             }),
             goal: maze.end,
-            queue: vec![],
+            queue: Frontier::new(maze),
+            explored: CellSet::new(maze),
         }
     }
 
-    fn heuristic(&self, coord: &Coord) -> u32 {
-        self.goal.manhattan_dist(coord)
+    fn heuristic(&self, maze: &Maze, coord: &Coord) -> u32 {
+        maze.distance(&self.goal, coord)
     }
 
     fn available_neighbours(&self, maze: &Maze) -> Vec<(Coord, Direction)> {
@@ -413,22 +631,9 @@ impl AStar {
 
         maze.connected_neighbours(&self.current.coord)
             .into_iter()
-            .filter(|(c, _)| !maze.explored.contains(&c))
-            .filter(|(c, _)| self.queue.iter().find(|hc| hc.coord == *c).is_none())
+            .filter(|(c, _)| !self.explored.contains(c))
             .collect()
     }
-
-    fn highlight_path_from_cell(hc: &AStarNode, maze: &mut Maze) {
-        maze.highlight_medium.insert(hc.coord);
-        if let Some(ref previous) = hc.previous {
-            AStar::highlight_path_from_cell(&previous, maze);
-        }
-    }
-
-    fn highlight_path(&self, maze: &mut Maze) {
-        maze.highlight_medium.clear();
-        AStar::highlight_path_from_cell(&self.current, maze);
-    }
 }
 
 impl Solver for AStar {
@@ -436,39 +641,46 @@ impl Solver for AStar {
         self.goal == self.current.coord
     }
 
-    fn tick(&mut self, maze: &mut Maze) -> Result<()> {
-        maze.highlight_bright.clear();
+    fn path(&self) -> Vec<Coord> {
+        let mut path = vec![];
+        let mut node = Some(&self.current);
+        while let Some(n) = node {
+            path.push(n.coord);
+            node = n.previous.as_ref();
+        }
+        path.reverse();
+
+        path
+    }
 
-        maze.explored.insert(self.current.coord);
+    fn tick(&mut self, maze: &Maze, observer: &mut dyn Observer) -> Result<()> {
+        if self.explored.insert(self.current.coord) {
+            observer.notify(Event::CellVisited(self.current.coord));
+        }
         for (neighbour, _) in self.available_neighbours(maze) {
             let dist_to_neighbour = self.current.dist + 1;
 
             let new_neighbour = AStarNode {
                 coord: neighbour,
                 dist: dist_to_neighbour,
-                score: dist_to_neighbour + self.heuristic(&neighbour),
+                score: dist_to_neighbour + self.heuristic(maze, &neighbour),
                 previous: Some(self.current.clone()),
             };
 
-            maze.highlight_dark.insert(neighbour);
-            match self
-                .queue
-                .binary_search_by_key(&new_neighbour.score, |n| n.score)
-            {
-                Ok(pos) | Err(pos) => {
-                    self.queue.insert(pos, Rc::new(new_neighbour));
-                }
+            if !self.queue.contains(&neighbour) {
+                observer.notify(Event::FrontierAdded(neighbour));
             }
+            self.queue
+                .push(neighbour, new_neighbour.score, Rc::new(new_neighbour));
         }
 
-        if self.queue.is_empty() {
-            return Err(Error::ImpossibleMaze);
-        }
-        self.current = self.queue.remove(0);
-        maze.highlight_dark.remove(&self.current.coord);
+        self.current = self.queue.pop().ok_or(Error::ImpossibleMaze)?;
+        observer.notify(Event::FrontierRemoved(self.current.coord));
 
-        maze.highlight_bright.insert(self.current.coord);
-        self.highlight_path(maze);
+        observer.notify(Event::Current(vec![self.current.coord]));
+        if observer.is_listening() {
+            observer.notify(Event::PathUpdated(self.path()));
+        }
 
         Ok(())
     }
@@ -0,0 +1,1973 @@
+use std;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use error::{Error, Result};
+use mask::Mask;
+use maze::{Coord, Direction, Wall};
+use random::Random;
+
+/// The shape of a maze: which cells exist, how they are connected and where they are drawn.
+///
+/// Every cell has a dense index in `0..cell_count()` and every wall that can be removed has a
+/// dense index in `0..edge_count()`, so mazes and algorithms can keep their state in bit sets.
+/// Cells are numbered floor by floor and row by row, so every row is a range of indices.
+/// Positions returned by the geometry methods are measured in cells.
+pub trait Topology: fmt::Debug + Send + Sync {
+    fn cell_count(&self) -> usize;
+
+    /// The index of a cell, or `None` if the coordinate isn't part of the maze
+    fn index(&self, coord: &Coord) -> Option<usize>;
+
+    /// The cell with the given index
+    fn coord(&self, idx: usize) -> Coord;
+
+    /// Every direction a cell has a wall in, including the border ones
+    fn directions(&self, coord: &Coord) -> Vec<Direction>;
+
+    /// The cell on the other side of the wall in `direction`, or `None` if that wall is part of
+    /// the border
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord>;
+
+    /// The wall of a cell in `direction`. Both cells sharing a wall get the same `Wall`, which
+    /// is kept by the cell to their north or west unless the topology says otherwise.
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall;
+
+    fn edge_count(&self) -> usize;
+
+    /// The index of a wall, or `None` for border walls
+    fn edge_index(&self, wall: &Wall) -> Option<usize>;
+
+    /// A lower bound of the number of steps between two cells
+    fn distance(&self, from: &Coord, to: &Coord) -> u32;
+
+    /// The width and height of the whole maze
+    fn size(&self) -> (f32, f32);
+
+    /// The corners of a cell
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)>;
+
+    /// The points of the line drawn for a wall
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)>;
+
+    /// The number of floors. Cells are drawn in the same place on every floor.
+    fn depth(&self) -> u32 {
+        1
+    }
+
+    /// The marker drawn in a cell with stairs going up or down
+    fn stair_shape(&self, _coord: &Coord, _direction: Direction) -> Vec<(f32, f32)> {
+        vec![]
+    }
+
+    /// Whether a wall can be removed, given which walls are standing right now. Only weave mazes
+    /// forbid removing some walls.
+    fn can_remove_wall(&self, _wall: &Wall, _has_wall: &dyn Fn(&Wall) -> bool) -> bool {
+        true
+    }
+
+    /// The walls a tunnel passes under, which are drawn with a gap. Walls that aren't tunnels
+    /// don't pass under anything.
+    fn tunnel_crossings(&self, _wall: &Wall) -> Vec<Wall> {
+        vec![]
+    }
+
+    /// The lines drawn for an open tunnel, under the cell it crosses
+    fn tunnel_lines(&self, _wall: &Wall) -> Vec<Vec<(f32, f32)>> {
+        vec![]
+    }
+
+    /// Every line drawn for a wall. Walls that wrap around the maze are drawn on both sides.
+    fn wall_lines(&self, wall: &Wall) -> Vec<Vec<(f32, f32)>> {
+        vec![self.wall_line(wall)]
+    }
+
+    /// Where mazes of this shape start and end unless told otherwise. `None` places them
+    /// randomly.
+    fn entrances(&self) -> Option<(Coord, Coord)> {
+        None
+    }
+
+    /// Every wall that can be removed, once
+    fn edges(&self) -> Vec<Wall> {
+        let mut edges = Vec::with_capacity(self.edge_count());
+        for idx in 0..self.cell_count() {
+            let coord = self.coord(idx);
+            for direction in self.directions(&coord) {
+                let wall = self.wall(&coord, direction);
+                if wall.removable() && wall.coord() == coord && wall.direction() == direction {
+                    edges.push(wall);
+                }
+            }
+        }
+
+        edges
+    }
+}
+
+fn row_major_index(width: u32, height: u32, depth: u32, coord: &Coord) -> Option<usize> {
+    let inside = |value: i32, max: u32| value >= 0 && value < max as i32;
+    if inside(coord.x, width) && inside(coord.y, height) && inside(coord.z, depth) {
+        let floor = coord.z as usize * height as usize;
+        Some((floor + coord.y as usize) * width as usize + coord.x as usize)
+    } else {
+        None
+    }
+}
+
+fn row_major_coord(width: u32, height: u32, idx: usize) -> Coord {
+    let (width, height) = (width as usize, height as usize);
+    let z = idx / (width * height);
+    let idx = idx % (width * height);
+    [(idx % width) as i32, (idx / width) as i32, z as i32].into()
+}
+
+/// A rectangular grid of square cells, optionally with several floors stacked on top of each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+    width: u32,
+    height: u32,
+    depth: u32,
+    wrap: Wrap,
+}
+
+impl Grid {
+    pub fn new(width: u32, height: u32) -> Grid {
+        Grid::wrapping(width, height, Wrap::None)
+    }
+
+    /// A grid whose borders wrap around to the opposite side
+    pub fn wrapping(width: u32, height: u32, wrap: Wrap) -> Grid {
+        Grid {
+            width,
+            height,
+            depth: 1,
+            wrap,
+        }
+    }
+
+    /// The same grid with `depth` floors, connected by stairs
+    pub fn with_depth(mut self, depth: u32) -> Grid {
+        self.depth = depth;
+        self
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    #[inline]
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    fn wraps_x(&self) -> bool {
+        self.wrap != Wrap::None
+    }
+
+    fn wraps_y(&self) -> bool {
+        self.wrap == Wrap::Torus
+    }
+
+    /// Edges between floors only exist when there's more than one
+    fn edges_per_cell(&self) -> usize {
+        if self.depth > 1 {
+            3
+        } else {
+            2
+        }
+    }
+}
+
+impl Topology for Grid {
+    fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize * self.depth as usize
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, self.depth, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    fn directions(&self, _coord: &Coord) -> Vec<Direction> {
+        let mut directions = vec![
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ];
+        if self.depth > 1 {
+            directions.push(Direction::Up);
+            directions.push(Direction::Down);
+        }
+
+        directions
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        match direction {
+            Direction::North
+            | Direction::East
+            | Direction::South
+            | Direction::West
+            | Direction::Up
+            | Direction::Down => {}
+            _ => return None,
+        }
+
+        self.index(coord)?;
+        let mut candidate = coord.step(direction);
+        if self.wraps_x() {
+            candidate.x = (candidate.x + self.width as i32) % self.width as i32;
+        }
+        if self.wraps_y() {
+            candidate.y = (candidate.y + self.height as i32) % self.height as i32;
+        }
+
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    /// Walls that wrap around are kept by the cell on the east or south edge, and floors by the
+    /// cell above them
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North | Direction::West | Direction::Up => {
+                    Wall::new(other, direction.opposite(), *coord, false)
+                }
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * self.edges_per_cell()
+    }
+
+    /// Two edges per cell, the ones to its east and to its south, and a third one below it when
+    /// there are several floors
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())? * self.edges_per_cell();
+        match wall.direction() {
+            Direction::East => Some(idx),
+            Direction::South => Some(idx + 1),
+            Direction::Down => Some(idx + 2),
+            _ => None,
+        }
+    }
+
+    /// The manhattan distance, going around the maze when that's shorter
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        let mut dx = (from.x - to.x).unsigned_abs();
+        let mut dy = (from.y - to.y).unsigned_abs();
+        let dz = (from.z - to.z).unsigned_abs();
+        if self.wraps_x() {
+            dx = dx.min(self.width - dx);
+        }
+        if self.wraps_y() {
+            dy = dy.min(self.height - dy);
+        }
+
+        dx + dy + dz
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let (x, y) = (coord.x as f32, coord.y as f32);
+        vec![(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)]
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let (x, y) = (wall.coord().x as f32, wall.coord().y as f32);
+
+        match wall.direction() {
+            Direction::North => vec![(x, y), (x + 1.0, y)],
+            Direction::East => vec![(x + 1.0, y), (x + 1.0, y + 1.0)],
+            Direction::South => vec![(x, y + 1.0), (x + 1.0, y + 1.0)],
+            Direction::West => vec![(x, y), (x, y + 1.0)],
+            // Square cells have no walls in the diagonal directions
+            _ => vec![],
+        }
+    }
+
+    fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// A triangle pointing up in the top half of the cell, or down in the bottom half
+    fn stair_shape(&self, coord: &Coord, direction: Direction) -> Vec<(f32, f32)> {
+        let (x, y) = (coord.x as f32, coord.y as f32);
+
+        match direction {
+            Direction::Up => vec![(x + 0.5, y + 0.15), (x + 0.7, y + 0.4), (x + 0.3, y + 0.4)],
+            Direction::Down => vec![(x + 0.3, y + 0.6), (x + 0.7, y + 0.6), (x + 0.5, y + 0.85)],
+            _ => vec![],
+        }
+    }
+
+    fn wall_lines(&self, wall: &Wall) -> Vec<Vec<(f32, f32)>> {
+        let (coord, other) = wall.divided_coords();
+        let mut lines = vec![self.wall_line(wall)];
+        if wall.removable() && coord.step(wall.direction()) != other {
+            let opposite = Wall::new(other, wall.direction().opposite(), coord, false);
+            lines.push(self.wall_line(&opposite));
+        }
+
+        lines
+    }
+}
+
+/// A grid of square cells with only the cells of a `Mask`, so mazes can have any outline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Masked {
+    grid: Grid,
+    /// The index of every cell of the grid that is part of the maze
+    indices: Vec<Option<usize>>,
+    coords: Vec<Coord>,
+}
+
+impl Masked {
+    /// Every cell of the mask has to be reachable from every other one
+    pub fn new(mask: &Mask) -> Result<Masked> {
+        let grid = Grid::new(mask.width(), mask.height());
+        let mut indices = vec![None; grid.cell_count()];
+        let mut coords = vec![];
+        for (idx, index) in indices.iter_mut().enumerate() {
+            let coord = grid.coord(idx);
+            if mask.contains(&coord) {
+                *index = Some(coords.len());
+                coords.push(coord);
+            }
+        }
+
+        let masked = Masked {
+            grid,
+            indices,
+            coords,
+        };
+        if !masked.is_connected() {
+            return Err(Error::InvalidMask(
+                "the cells of the mask aren't all connected".to_string(),
+            ));
+        }
+
+        Ok(masked)
+    }
+
+    fn is_connected(&self) -> bool {
+        let mut reached = vec![false; self.cell_count()];
+        let mut pending = self.coords.first().cloned().into_iter().collect::<Vec<_>>();
+        let mut count = 0;
+        while let Some(coord) = pending.pop() {
+            let idx = self.index(&coord).unwrap();
+            if reached[idx] {
+                continue;
+            }
+            reached[idx] = true;
+            count += 1;
+
+            for direction in self.directions(&coord) {
+                pending.extend(self.neighbour(&coord, direction));
+            }
+        }
+
+        count == self.cell_count()
+    }
+}
+
+impl Topology for Masked {
+    fn cell_count(&self) -> usize {
+        self.coords.len()
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        self.indices[self.grid.index(coord)?]
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        self.coords[idx]
+    }
+
+    fn directions(&self, coord: &Coord) -> Vec<Direction> {
+        self.grid.directions(coord)
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        self.index(coord)?;
+        let candidate = self.grid.neighbour(coord, direction)?;
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    /// Walls next to cells outside the mask are part of the border
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North | Direction::West => {
+                    Wall::new(other, direction.opposite(), *coord, false)
+                }
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 2
+    }
+
+    /// Two edges per cell, the ones to its east and to its south
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())?;
+        match wall.direction() {
+            Direction::East => Some(idx * 2),
+            Direction::South => Some(idx * 2 + 1),
+            _ => None,
+        }
+    }
+
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        self.grid.distance(from, to)
+    }
+
+    fn size(&self) -> (f32, f32) {
+        self.grid.size()
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        self.grid.cell_shape(coord)
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        self.grid.wall_line(wall)
+    }
+}
+
+/// The height of a hexagon that is one cell wide
+const HEX_HEIGHT: f32 = 0.866_025_4;
+
+/// A grid of flat topped hexagons, in columns where every odd column is shifted half a cell down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex {
+    width: u32,
+    height: u32,
+}
+
+impl Hex {
+    pub fn new(width: u32, height: u32) -> Hex {
+        Hex { width, height }
+    }
+
+    /// The same cell in cube coordinates, where distances are easy to compute
+    fn cube(coord: &Coord) -> (i32, i32, i32) {
+        let x = coord.x;
+        let z = coord.y - (coord.x - (coord.x & 1)) / 2;
+
+        (x, -x - z, z)
+    }
+
+    fn center(coord: &Coord) -> (f32, f32) {
+        let shift = if coord.x & 1 == 1 { 0.5 } else { 0.0 };
+
+        (
+            0.5 + coord.x as f32 * 0.75,
+            (coord.y as f32 + 0.5 + shift) * HEX_HEIGHT,
+        )
+    }
+}
+
+impl Topology for Hex {
+    fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, 1, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    fn directions(&self, _coord: &Coord) -> Vec<Direction> {
+        vec![
+            Direction::North,
+            Direction::NorthEast,
+            Direction::SouthEast,
+            Direction::South,
+            Direction::SouthWest,
+            Direction::NorthWest,
+        ]
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        // Odd columns are shifted down, so their diagonal neighbours are one row lower
+        let shift = coord.x & 1;
+        let (dx, dy) = match direction {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, shift - 1),
+            Direction::SouthEast => (1, shift),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, shift),
+            Direction::NorthWest => (-1, shift - 1),
+            _ => return None,
+        };
+
+        let candidate = Coord {
+            x: coord.x + dx,
+            y: coord.y + dy,
+            z: coord.z,
+        };
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North | Direction::NorthWest | Direction::SouthWest => {
+                    Wall::new(other, direction.opposite(), *coord, false)
+                }
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 3
+    }
+
+    /// Three edges per cell, the ones to its south, south east and north east
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())?;
+        match wall.direction() {
+            Direction::South => Some(idx * 3),
+            Direction::SouthEast => Some(idx * 3 + 1),
+            Direction::NorthEast => Some(idx * 3 + 2),
+            _ => None,
+        }
+    }
+
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        let (x1, y1, z1) = Hex::cube(from);
+        let (x2, y2, z2) = Hex::cube(to);
+
+        ((x1 - x2).abs().max((y1 - y2).abs()).max((z1 - z2).abs())) as u32
+    }
+
+    fn size(&self) -> (f32, f32) {
+        let shift = if self.width > 1 { 0.5 } else { 0.0 };
+
+        (
+            0.25 + self.width as f32 * 0.75,
+            (self.height as f32 + shift) * HEX_HEIGHT,
+        )
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let (x, y) = Hex::center(coord);
+        let h = HEX_HEIGHT / 2.0;
+
+        vec![
+            (x + 0.5, y),
+            (x + 0.25, y + h),
+            (x - 0.25, y + h),
+            (x - 0.5, y),
+            (x - 0.25, y - h),
+            (x + 0.25, y - h),
+        ]
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let corners = self.cell_shape(&wall.coord());
+        let (from, to) = match wall.direction() {
+            Direction::SouthEast => (0, 1),
+            Direction::South => (1, 2),
+            Direction::SouthWest => (2, 3),
+            Direction::NorthWest => (3, 4),
+            Direction::North => (4, 5),
+            Direction::NorthEast => (5, 0),
+            _ => return vec![],
+        };
+
+        vec![corners[from], corners[to]]
+    }
+}
+
+/// The height of a triangle with sides one cell long
+const DELTA_HEIGHT: f32 = 0.866_025_4;
+
+/// A grid of triangles, alternating between ones pointing up and ones pointing down. Every
+/// triangle is half a cell to the right of the previous one in its row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delta {
+    width: u32,
+    height: u32,
+}
+
+impl Delta {
+    pub fn new(width: u32, height: u32) -> Delta {
+        Delta { width, height }
+    }
+
+    pub fn points_up(coord: &Coord) -> bool {
+        (coord.x + coord.y) % 2 == 0
+    }
+}
+
+impl Topology for Delta {
+    fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, 1, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    /// Triangles pointing up have a wall to the south, the ones pointing down to the north
+    fn directions(&self, coord: &Coord) -> Vec<Direction> {
+        if Delta::points_up(coord) {
+            vec![Direction::East, Direction::South, Direction::West]
+        } else {
+            vec![Direction::North, Direction::East, Direction::West]
+        }
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        let valid = match direction {
+            Direction::North => !Delta::points_up(coord),
+            Direction::South => Delta::points_up(coord),
+            Direction::East | Direction::West => true,
+            _ => false,
+        };
+        if !valid {
+            return None;
+        }
+
+        let candidate = coord.step(direction);
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North | Direction::West => {
+                    Wall::new(other, direction.opposite(), *coord, false)
+                }
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 2
+    }
+
+    /// Two edges per cell, the ones to its east and to its south
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())?;
+        match wall.direction() {
+            Direction::East => Some(idx * 2),
+            Direction::South => Some(idx * 2 + 1),
+            _ => None,
+        }
+    }
+
+    /// Every step changes either the row or the column. Going through several rows in the same
+    /// direction also needs a step sideways between each of them, to turn the triangle around.
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        let dx = (from.x - to.x).abs();
+        let dy = (from.y - to.y).abs();
+
+        (dy + dx.max(dy - 1)) as u32
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (
+            (self.width + 1) as f32 * 0.5,
+            self.height as f32 * DELTA_HEIGHT,
+        )
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let x = coord.x as f32 * 0.5;
+        let top = coord.y as f32 * DELTA_HEIGHT;
+        let bottom = top + DELTA_HEIGHT;
+
+        if Delta::points_up(coord) {
+            vec![(x + 0.5, top), (x + 1.0, bottom), (x, bottom)]
+        } else {
+            vec![(x, top), (x + 1.0, top), (x + 0.5, bottom)]
+        }
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let corners = self.cell_shape(&wall.coord());
+        let (from, to) = match (Delta::points_up(&wall.coord()), wall.direction()) {
+            (true, Direction::East) => (0, 1),
+            (true, Direction::South) => (1, 2),
+            (true, Direction::West) => (2, 0),
+            (false, Direction::North) => (0, 1),
+            (false, Direction::East) => (1, 2),
+            (false, Direction::West) => (2, 0),
+            _ => return vec![],
+        };
+
+        vec![corners[from], corners[to]]
+    }
+}
+
+/// How far the sides of an octagon are from its centre, half the distance between two diagonal
+/// neighbours
+const OCTAGON_APOTHEM: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// How far the corners of an octagon are from its centre
+const OCTAGON_RADIUS: f32 = 0.765_366_9;
+/// Half the side of the small squares between octagons
+const UPSILON_SQUARE: f32 = 1.0 - OCTAGON_APOTHEM;
+
+/// A tiling of octagons and small squares, alternating like a checkerboard. Octagons touch the
+/// octagons on their diagonals and the squares on their sides, squares only touch the four
+/// octagons around them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Upsilon {
+    width: u32,
+    height: u32,
+}
+
+impl Upsilon {
+    pub fn new(width: u32, height: u32) -> Upsilon {
+        Upsilon { width, height }
+    }
+
+    pub fn is_octagon(coord: &Coord) -> bool {
+        (coord.x + coord.y) % 2 == 0
+    }
+
+    fn center(coord: &Coord) -> (f32, f32) {
+        (
+            coord.x as f32 + OCTAGON_APOTHEM,
+            coord.y as f32 + OCTAGON_APOTHEM,
+        )
+    }
+}
+
+impl Topology for Upsilon {
+    fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, 1, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    fn directions(&self, coord: &Coord) -> Vec<Direction> {
+        if Upsilon::is_octagon(coord) {
+            vec![
+                Direction::North,
+                Direction::NorthEast,
+                Direction::East,
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::West,
+                Direction::NorthWest,
+            ]
+        } else {
+            vec![
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ]
+        }
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        match direction {
+            Direction::North | Direction::East | Direction::South | Direction::West => {}
+            Direction::NorthEast
+            | Direction::SouthEast
+            | Direction::SouthWest
+            | Direction::NorthWest
+                if Upsilon::is_octagon(coord) => {}
+            _ => return None,
+        }
+
+        self.index(coord)?;
+        let candidate = coord.step(direction);
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North
+                | Direction::West
+                | Direction::NorthWest
+                | Direction::NorthEast => Wall::new(other, direction.opposite(), *coord, false),
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 4
+    }
+
+    /// Four edges per cell, the ones to its east, south, south east and south west. Squares only
+    /// use the first two.
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())?;
+        match wall.direction() {
+            Direction::East => Some(idx * 4),
+            Direction::South => Some(idx * 4 + 1),
+            Direction::SouthEast => Some(idx * 4 + 2),
+            Direction::SouthWest => Some(idx * 4 + 3),
+            _ => None,
+        }
+    }
+
+    /// Octagons move diagonally in a single step, so no path is shorter than the longest axis
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        let dx = (from.x - to.x).unsigned_abs();
+        let dy = (from.y - to.y).unsigned_abs();
+
+        dx.max(dy)
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (
+            self.width as f32 - 1.0 + 2.0 * OCTAGON_APOTHEM,
+            self.height as f32 - 1.0 + 2.0 * OCTAGON_APOTHEM,
+        )
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let (x, y) = Upsilon::center(coord);
+
+        if Upsilon::is_octagon(coord) {
+            (0..8)
+                .map(|i| {
+                    let angle = (i as f32 - 0.5) * std::f32::consts::FRAC_PI_4;
+                    (
+                        x + OCTAGON_RADIUS * angle.cos(),
+                        y + OCTAGON_RADIUS * angle.sin(),
+                    )
+                })
+                .collect()
+        } else {
+            let h = UPSILON_SQUARE;
+            vec![
+                (x + h, y - h),
+                (x + h, y + h),
+                (x - h, y + h),
+                (x - h, y - h),
+            ]
+        }
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let coord = wall.coord();
+        let corners = self.cell_shape(&coord);
+
+        if Upsilon::is_octagon(&coord) {
+            // Sides go clockwise from the east one
+            let side = match wall.direction() {
+                Direction::East => 0,
+                Direction::SouthEast => 1,
+                Direction::South => 2,
+                Direction::SouthWest => 3,
+                Direction::West => 4,
+                Direction::NorthWest => 5,
+                Direction::North => 6,
+                Direction::NorthEast => 7,
+                _ => return vec![],
+            };
+            vec![corners[side], corners[(side + 1) % 8]]
+        } else {
+            let (from, to) = match wall.direction() {
+                Direction::East => (0, 1),
+                Direction::South => (1, 2),
+                Direction::West => (2, 3),
+                Direction::North => (3, 0),
+                _ => return vec![],
+            };
+            vec![corners[from], corners[to]]
+        }
+    }
+}
+
+/// A circular maze of concentric rings around a single centre cell. Rings are one cell thick and
+/// every ring has as many cells as fit without them getting much wider than they are thick, so
+/// cells can have several outward neighbours.
+///
+/// Coordinates are `(column, ring)`, with columns going clockwise from the east.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polar {
+    /// The number of cells in each ring
+    counts: Vec<u32>,
+    /// The index of the first cell of each ring
+    offsets: Vec<usize>,
+}
+
+impl Polar {
+    pub fn new(rings: u32) -> Polar {
+        let mut counts: Vec<u32> = vec![1];
+        for ring in 1..rings {
+            let previous = counts[ring as usize - 1];
+            let circumference = 2.0 * std::f64::consts::PI * f64::from(ring);
+            let ratio = (circumference / f64::from(previous)).round().max(1.0) as u32;
+            counts.push(previous * ratio);
+        }
+
+        let mut offsets = Vec::with_capacity(counts.len());
+        let mut offset = 0;
+        for count in &counts {
+            offsets.push(offset);
+            offset += *count as usize;
+        }
+
+        Polar { counts, offsets }
+    }
+
+    pub fn rings(&self) -> u32 {
+        self.counts.len() as u32
+    }
+
+    /// How many cells of `ring` there are for each cell of the ring inside it
+    fn ratio(&self, ring: usize) -> u32 {
+        self.counts[ring] / self.counts[ring - 1]
+    }
+
+    /// The angles at which a cell starts and ends
+    fn angles(&self, coord: &Coord) -> (f32, f32) {
+        let step = 2.0 * std::f32::consts::PI / self.counts[coord.y as usize] as f32;
+        (coord.x as f32 * step, (coord.x + 1) as f32 * step)
+    }
+
+    fn point(&self, radius: f32, angle: f32) -> (f32, f32) {
+        let center = self.rings() as f32;
+        (center + radius * angle.cos(), center + radius * angle.sin())
+    }
+
+    fn arc(&self, radius: f32, from: f32, to: f32) -> Vec<(f32, f32)> {
+        let segments = (((to - from).abs() * radius * 4.0).ceil() as usize).max(1);
+        (0..=segments)
+            .map(|i| self.point(radius, from + (to - from) * i as f32 / segments as f32))
+            .collect()
+    }
+}
+
+impl Topology for Polar {
+    fn cell_count(&self) -> usize {
+        match self.offsets.last() {
+            Some(offset) => offset + *self.counts.last().unwrap() as usize,
+            None => 0,
+        }
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        if coord.z != 0 || coord.y < 0 || coord.y >= self.rings() as i32 {
+            return None;
+        }
+
+        let ring = coord.y as usize;
+        if coord.x >= 0 && coord.x < self.counts[ring] as i32 {
+            Some(self.offsets[ring] + coord.x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        let ring = match self.offsets.binary_search(&idx) {
+            Ok(ring) => ring,
+            Err(ring) => ring - 1,
+        };
+
+        [(idx - self.offsets[ring]) as i32, ring as i32].into()
+    }
+
+    /// Cells on the rim have a single outward wall, which is part of the border
+    fn directions(&self, coord: &Coord) -> Vec<Direction> {
+        let ring = coord.y as usize;
+        let mut directions = vec![];
+        if ring > 0 {
+            directions.push(Direction::Inward);
+            directions.push(Direction::Clockwise);
+            directions.push(Direction::CounterClockwise);
+        }
+
+        let outward = if ring + 1 < self.counts.len() {
+            self.ratio(ring + 1)
+        } else {
+            1
+        };
+        for idx in 0..outward {
+            directions.push(Direction::Outward(idx));
+        }
+
+        directions
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        self.index(coord)?;
+        let ring = coord.y as usize;
+        let count = self.counts[ring] as i32;
+
+        match direction {
+            Direction::Inward if ring > 0 => {
+                Some([coord.x / self.ratio(ring) as i32, coord.y - 1].into())
+            }
+            Direction::Outward(idx) if ring + 1 < self.counts.len() => {
+                let ratio = self.ratio(ring + 1);
+                if idx < ratio {
+                    Some([coord.x * ratio as i32 + idx as i32, coord.y + 1].into())
+                } else {
+                    None
+                }
+            }
+            Direction::Clockwise if ring > 0 => Some([(coord.x + 1) % count, coord.y].into()),
+            Direction::CounterClockwise if ring > 0 => {
+                Some([(coord.x + count - 1) % count, coord.y].into())
+            }
+            _ => None,
+        }
+    }
+
+    /// Walls between two rings are kept by the outer cell, walls between two cells of the same
+    /// ring by the one counter clockwise from it
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::Outward(_) | Direction::CounterClockwise => {
+                    Wall::new(other, direction.opposite(), *coord, false)
+                }
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 2
+    }
+
+    /// Two edges per cell, the ones clockwise and inward from it
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())?;
+        match wall.direction() {
+            Direction::Clockwise => Some(idx * 2),
+            Direction::Inward => Some(idx * 2 + 1),
+            _ => None,
+        }
+    }
+
+    /// Every step changes the ring by one at most
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        (from.y - to.y).unsigned_abs()
+    }
+
+    fn size(&self) -> (f32, f32) {
+        let diameter = 2.0 * self.rings() as f32;
+        (diameter, diameter)
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let radius = coord.y as f32;
+        let (from, to) = self.angles(coord);
+
+        let mut shape = self.arc(radius + 1.0, to, from);
+        if coord.y > 0 {
+            shape.extend(self.arc(radius, from, to));
+        }
+
+        shape
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let coord = wall.coord();
+        let radius = coord.y as f32;
+        let (from, to) = self.angles(&coord);
+
+        match wall.direction() {
+            Direction::Inward => self.arc(radius, from, to),
+            Direction::Outward(_) => self.arc(radius + 1.0, from, to),
+            Direction::Clockwise => vec![self.point(radius, to), self.point(radius + 1.0, to)],
+            Direction::CounterClockwise => {
+                vec![self.point(radius, from), self.point(radius + 1.0, from)]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// From the centre to the last cell of the rim
+    fn entrances(&self) -> Option<(Coord, Coord)> {
+        Some((self.coord(0), self.coord(self.cell_count() - 1)))
+    }
+}
+
+/// A grid of square cells where a passage can tunnel under a neighbour to the cell past it, when
+/// that neighbour is a straight corridor going the other way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weave {
+    width: u32,
+    height: u32,
+}
+
+impl Weave {
+    pub fn new(width: u32, height: u32) -> Weave {
+        Weave { width, height }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The tunnel going the same way as a direction
+    fn under(direction: Direction) -> Direction {
+        match direction {
+            Direction::North => Direction::UnderNorth,
+            Direction::East => Direction::UnderEast,
+            Direction::South => Direction::UnderSouth,
+            Direction::West => Direction::UnderWest,
+            other => other,
+        }
+    }
+
+    /// The direction a tunnel goes
+    fn over(direction: Direction) -> Direction {
+        match direction {
+            Direction::UnderNorth => Direction::North,
+            Direction::UnderEast => Direction::East,
+            Direction::UnderSouth => Direction::South,
+            Direction::UnderWest => Direction::West,
+            other => other,
+        }
+    }
+
+    fn across(direction: Direction) -> [Direction; 2] {
+        match direction {
+            Direction::North | Direction::South => [Direction::East, Direction::West],
+            _ => [Direction::North, Direction::South],
+        }
+    }
+}
+
+impl Topology for Weave {
+    fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, 1, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    fn directions(&self, _coord: &Coord) -> Vec<Direction> {
+        vec![
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+            Direction::UnderNorth,
+            Direction::UnderEast,
+            Direction::UnderSouth,
+            Direction::UnderWest,
+        ]
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        match Weave::over(direction) {
+            Direction::North | Direction::East | Direction::South | Direction::West => {}
+            _ => return None,
+        }
+
+        self.index(coord)?;
+        let candidate = coord.step(direction);
+        self.index(&candidate).map(|_| candidate)
+    }
+
+    /// Tunnels are kept the same way as walls
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.neighbour(coord, direction) {
+            Some(other) => match direction {
+                Direction::North
+                | Direction::West
+                | Direction::UnderNorth
+                | Direction::UnderWest => Wall::new(other, direction.opposite(), *coord, false),
+                _ => Wall::new(*coord, direction, other, false),
+            },
+            None => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.cell_count() * 4
+    }
+
+    /// Four edges per cell, the walls to its east and south and the tunnels going east and south
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let idx = self.index(&wall.coord())? * 4;
+        match wall.direction() {
+            Direction::East => Some(idx),
+            Direction::South => Some(idx + 1),
+            Direction::UnderEast => Some(idx + 2),
+            Direction::UnderSouth => Some(idx + 3),
+            _ => None,
+        }
+    }
+
+    /// Every step covers two cells at most, when going through a tunnel
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        from.manhattan_dist(to).div_ceil(2)
+    }
+
+    /// Every side of a cell takes a single passage, either through the wall or through a tunnel
+    /// going under one of the cells. Tunnels also need the cell they go under to be a corridor
+    /// going the other way.
+    fn can_remove_wall(&self, wall: &Wall, has_wall: &dyn Fn(&Wall) -> bool) -> bool {
+        let coord = wall.coord();
+        let direction = Weave::over(wall.direction());
+        let tunnel = Weave::under(direction);
+        let behind = coord.step(direction.opposite());
+
+        match wall.direction() {
+            Direction::East | Direction::South => {
+                has_wall(&self.wall(&coord, tunnel)) && has_wall(&self.wall(&behind, tunnel))
+            }
+            Direction::UnderEast | Direction::UnderSouth => {
+                let under = coord.step(direction);
+                let sides_free = has_wall(&self.wall(&coord, direction))
+                    && has_wall(&self.wall(&under, direction))
+                    && has_wall(&self.wall(&behind, tunnel))
+                    && has_wall(&self.wall(&under, tunnel));
+                let corridor = Weave::across(direction)
+                    .iter()
+                    .all(|across| !has_wall(&self.wall(&under, *across)));
+
+                sides_free && corridor
+            }
+            _ => true,
+        }
+    }
+
+    fn tunnel_crossings(&self, wall: &Wall) -> Vec<Wall> {
+        match wall.direction() {
+            Direction::UnderEast | Direction::UnderSouth => {
+                let direction = Weave::over(wall.direction());
+                let under = wall.coord().step(direction);
+                vec![
+                    self.wall(&wall.coord(), direction),
+                    self.wall(&under, direction),
+                ]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Dashed lines across the cell the tunnel goes under, lined up with the gaps in its walls
+    fn tunnel_lines(&self, wall: &Wall) -> Vec<Vec<(f32, f32)>> {
+        let under = wall.coord().step(Weave::over(wall.direction()));
+        let (x, y) = (under.x as f32, under.y as f32);
+        let dashes = [(0.0, 0.25), (0.375, 0.625), (0.75, 1.0)];
+        let rails = [1.0 / 3.0, 2.0 / 3.0];
+
+        let mut lines = vec![];
+        for rail in &rails {
+            for (from, to) in &dashes {
+                lines.push(match wall.direction() {
+                    Direction::UnderEast => vec![(x + from, y + rail), (x + to, y + rail)],
+                    Direction::UnderSouth => vec![(x + rail, y + from), (x + rail, y + to)],
+                    _ => return vec![],
+                });
+            }
+        }
+
+        lines
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        let (x, y) = (coord.x as f32, coord.y as f32);
+        vec![(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)]
+    }
+
+    /// Tunnels are only drawn once they're open
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        let (x, y) = (wall.coord().x as f32, wall.coord().y as f32);
+
+        match wall.direction() {
+            Direction::North => vec![(x, y), (x + 1.0, y)],
+            Direction::East => vec![(x + 1.0, y), (x + 1.0, y + 1.0)],
+            Direction::South => vec![(x, y + 1.0), (x + 1.0, y + 1.0)],
+            Direction::West => vec![(x, y), (x, y + 1.0)],
+            _ => vec![],
+        }
+    }
+}
+
+/// One side of a Voronoi cell
+#[derive(Debug, Clone, PartialEq)]
+struct Side {
+    from: (f32, f32),
+    to: (f32, f32),
+    /// The cell on the other side, `None` for the border
+    neighbour: Option<usize>,
+    /// The number of the same side in the neighbour
+    back: usize,
+    edge: usize,
+}
+
+/// How far apart, in cells, two points can be and still change each other's region. Every cell
+/// of the grid has a point away from its edges, so no region reaches further than that.
+const VORONOI_REACH: i32 = 3;
+/// Sides shorter than this are dropped
+const VORONOI_EPSILON: f32 = 1e-4;
+
+/// Irregular cells, the Voronoi regions around one random point in every cell of a grid. Two
+/// cells are neighbours when their regions share a side, which happens when their points are
+/// joined in the Delaunay triangulation.
+///
+/// Coordinates are the ones of the grid cell the point was placed in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Voronoi {
+    width: u32,
+    height: u32,
+    points: Vec<(f32, f32)>,
+    /// The sides of every cell, clockwise
+    sides: Vec<Vec<Side>>,
+    edge_count: usize,
+    /// The longest distance between the points of two neighbours
+    longest_step: f32,
+}
+
+impl Voronoi {
+    /// The points are drawn from `random`, so the same random numbers always lay out the same
+    /// cells
+    pub fn new(width: u32, height: u32, random: &mut Random) -> Voronoi {
+        let mut points = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let dx = 0.1 + 0.8 * random.unit();
+                let dy = 0.1 + 0.8 * random.unit();
+                points.push((x as f32 + dx, y as f32 + dy));
+            }
+        }
+
+        let regions: Vec<_> = (0..points.len())
+            .map(|idx| Voronoi::region(width, height, &points, idx))
+            .collect();
+
+        let mut sides: Vec<Vec<Side>> = Vec::with_capacity(points.len());
+        let mut edge_count = 0;
+        let mut longest_step: f32 = 0.0;
+        for (idx, region) in regions.iter().enumerate() {
+            let mut cell_sides = Vec::with_capacity(region.len());
+            for (i, &(from, neighbour)) in region.iter().enumerate() {
+                let to = region[(i + 1) % region.len()].0;
+                // Only keep the neighbours that agree, rounding can leave a tiny side on one of
+                // them
+                let back = neighbour.and_then(|other| {
+                    regions[other]
+                        .iter()
+                        .position(|&(_, n)| n == Some(idx))
+                        .map(|back| (other, back))
+                });
+
+                let side = match back {
+                    Some((other, back)) => {
+                        let edge = if other < idx {
+                            sides[other][back].edge
+                        } else {
+                            edge_count += 1;
+                            edge_count - 1
+                        };
+                        longest_step = longest_step.max(point_distance(points[idx], points[other]));
+
+                        Side {
+                            from,
+                            to,
+                            neighbour: Some(other),
+                            back,
+                            edge,
+                        }
+                    }
+                    None => Side {
+                        from,
+                        to,
+                        neighbour: None,
+                        back: 0,
+                        edge: 0,
+                    },
+                };
+                cell_sides.push(side);
+            }
+            sides.push(cell_sides);
+        }
+
+        Voronoi {
+            width,
+            height,
+            points,
+            sides,
+            edge_count,
+            longest_step,
+        }
+    }
+
+    /// The corners of the region closest to a point, each with the cell on the other side of the
+    /// side starting there. The whole maze is clipped by the bisector with every nearby point.
+    fn region(
+        width: u32,
+        height: u32,
+        points: &[(f32, f32)],
+        idx: usize,
+    ) -> Vec<((f32, f32), Option<usize>)> {
+        let (w, h) = (width as f32, height as f32);
+        let mut region = vec![
+            ((0.0, 0.0), None),
+            ((w, 0.0), None),
+            ((w, h), None),
+            ((0.0, h), None),
+        ];
+
+        let coord = row_major_coord(width, height, idx);
+        let point = points[idx];
+        for y in coord.y - VORONOI_REACH..=coord.y + VORONOI_REACH {
+            for x in coord.x - VORONOI_REACH..=coord.x + VORONOI_REACH {
+                let other = match row_major_index(width, height, 1, &[x, y].into()) {
+                    Some(other) if other != idx => other,
+                    _ => continue,
+                };
+                region = Voronoi::clip(&region, point, points[other], other);
+            }
+        }
+
+        let len = region.len();
+        (0..len)
+            .filter(|&i| point_distance(region[i].0, region[(i + 1) % len].0) > VORONOI_EPSILON)
+            .map(|i| region[i])
+            .collect()
+    }
+
+    /// The part of a region closer to `point` than to `other`, which is cell `neighbour`
+    fn clip(
+        region: &[((f32, f32), Option<usize>)],
+        point: (f32, f32),
+        other: (f32, f32),
+        neighbour: usize,
+    ) -> Vec<((f32, f32), Option<usize>)> {
+        let middle = ((point.0 + other.0) / 2.0, (point.1 + other.1) / 2.0);
+        let normal = (other.0 - point.0, other.1 - point.1);
+        // Positive on the side of `other`
+        let side = |p: (f32, f32)| (p.0 - middle.0) * normal.0 + (p.1 - middle.1) * normal.1;
+
+        let mut clipped = Vec::with_capacity(region.len() + 1);
+        for (i, &(from, tag)) in region.iter().enumerate() {
+            let to = region[(i + 1) % region.len()].0;
+            let (a, b) = (side(from), side(to));
+            let crossing = || {
+                let t = a / (a - b);
+                (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+            };
+
+            match (a <= 0.0, b <= 0.0) {
+                (true, true) => clipped.push((from, tag)),
+                (true, false) => {
+                    clipped.push((from, tag));
+                    clipped.push((crossing(), Some(neighbour)));
+                }
+                (false, true) => clipped.push((crossing(), tag)),
+                (false, false) => {}
+            }
+        }
+
+        clipped
+    }
+
+    fn side(&self, coord: &Coord, direction: Direction) -> Option<&Side> {
+        match direction {
+            Direction::Side(side) => self.sides[self.index(coord)?].get(side as usize),
+            _ => None,
+        }
+    }
+}
+
+fn point_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+impl Topology for Voronoi {
+    fn cell_count(&self) -> usize {
+        self.points.len()
+    }
+
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        row_major_index(self.width, self.height, 1, coord)
+    }
+
+    fn coord(&self, idx: usize) -> Coord {
+        row_major_coord(self.width, self.height, idx)
+    }
+
+    fn directions(&self, coord: &Coord) -> Vec<Direction> {
+        let count = self.index(coord).map_or(0, |idx| self.sides[idx].len());
+        (0..count as u32).map(Direction::Side).collect()
+    }
+
+    fn neighbour(&self, coord: &Coord, direction: Direction) -> Option<Coord> {
+        let neighbour = self.side(coord, direction)?.neighbour?;
+        Some(self.coord(neighbour))
+    }
+
+    /// Walls between two cells are kept by the cell with the lowest index
+    fn wall(&self, coord: &Coord, direction: Direction) -> Wall {
+        match self.side(coord, direction) {
+            Some(&Side {
+                neighbour: Some(other),
+                back,
+                ..
+            }) => {
+                let other = self.coord(other);
+                if self.index(&other) < self.index(coord) {
+                    Wall::new(other, Direction::Side(back as u32), *coord, false)
+                } else {
+                    Wall::new(*coord, direction, other, false)
+                }
+            }
+            _ => Wall::new(*coord, direction, coord.step(direction), true),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn edge_index(&self, wall: &Wall) -> Option<usize> {
+        if !wall.removable() {
+            return None;
+        }
+
+        let side = self.side(&wall.coord(), wall.direction())?;
+        side.neighbour.map(|_| side.edge)
+    }
+
+    /// No step between neighbours is longer than the longest one
+    fn distance(&self, from: &Coord, to: &Coord) -> u32 {
+        match (self.index(from), self.index(to)) {
+            (Some(from), Some(to)) if self.longest_step > 0.0 => {
+                let steps = point_distance(self.points[from], self.points[to]) / self.longest_step;
+                // Rounding could push an exact number of steps over the next integer
+                (steps - 1e-3).ceil().max(0.0) as u32
+            }
+            _ => 0,
+        }
+    }
+
+    fn size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    fn cell_shape(&self, coord: &Coord) -> Vec<(f32, f32)> {
+        match self.index(coord) {
+            Some(idx) => self.sides[idx].iter().map(|side| side.from).collect(),
+            None => vec![],
+        }
+    }
+
+    fn wall_line(&self, wall: &Wall) -> Vec<(f32, f32)> {
+        match self.side(&wall.coord(), wall.direction()) {
+            Some(side) => vec![side.from, side.to],
+            None => vec![],
+        }
+    }
+}
+
+/// Which borders of a grid wrap around to the opposite side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    None,
+    /// The east and west borders are joined
+    Cylinder,
+    /// The east and west borders are joined, and so are the north and south ones
+    Torus,
+}
+
+impl Wrap {
+    pub fn all() -> [Wrap; 3] {
+        [Wrap::None, Wrap::Cylinder, Wrap::Torus]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Wrap::None => "none",
+            Wrap::Cylinder => "cylinder",
+            Wrap::Torus => "torus",
+        }
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lowercase = s.to_lowercase();
+        Wrap::all()
+            .iter()
+            .find(|w| w.name() == lowercase)
+            .cloned()
+            .ok_or_else(|| Error::UnsupportedWrap(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopologyType {
+    Grid,
+    Hex,
+    Delta,
+    Polar,
+    Weave,
+    Upsilon,
+    Voronoi,
+}
+
+impl TopologyType {
+    pub fn all() -> [TopologyType; 7] {
+        [
+            TopologyType::Grid,
+            TopologyType::Hex,
+            TopologyType::Delta,
+            TopologyType::Polar,
+            TopologyType::Weave,
+            TopologyType::Upsilon,
+            TopologyType::Voronoi,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TopologyType::Grid => "grid",
+            TopologyType::Hex => "hex",
+            TopologyType::Delta => "delta",
+            TopologyType::Polar => "polar",
+            TopologyType::Weave => "weave",
+            TopologyType::Upsilon => "upsilon",
+            TopologyType::Voronoi => "voronoi",
+        }
+    }
+
+    /// A `width` x `height` x `depth` topology of this type. Polar mazes have `height` rings and
+    /// no width.
+    ///
+    /// Only grids can wrap around or have more than one floor. Voronoi mazes draw the position of
+    /// their cells from `layout`.
+    pub fn init(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+        wrap: Wrap,
+        layout: &mut Random,
+    ) -> Result<Arc<dyn Topology>> {
+        if *self != TopologyType::Grid {
+            if wrap != Wrap::None {
+                return Err(Error::UnsupportedShape("Wrapping".to_string()));
+            }
+            if depth != 1 {
+                return Err(Error::UnsupportedShape("Several floors".to_string()));
+            }
+        }
+
+        Ok(match *self {
+            TopologyType::Grid => Arc::new(Grid::wrapping(width, height, wrap).with_depth(depth)),
+            TopologyType::Hex => Arc::new(Hex::new(width, height)),
+            TopologyType::Delta => Arc::new(Delta::new(width, height)),
+            TopologyType::Polar => Arc::new(Polar::new(height)),
+            TopologyType::Weave => Arc::new(Weave::new(width, height)),
+            TopologyType::Upsilon => Arc::new(Upsilon::new(width, height)),
+            TopologyType::Voronoi => Arc::new(Voronoi::new(width, height, layout)),
+        })
+    }
+}
+
+impl FromStr for TopologyType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lowercase = s.to_lowercase();
+        TopologyType::all()
+            .iter()
+            .find(|t| t.name() == lowercase)
+            .cloned()
+            .ok_or_else(|| Error::UnsupportedTopology(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::{Seed, Stream};
+    use std::collections::HashMap;
+
+    /// Checks that every cell round-trips through its index and that every wall between two
+    /// cells is the same seen from either of them, with its own edge index
+    fn assert_consistent(topology: &dyn Topology) {
+        let mut edges = HashMap::new();
+
+        for idx in 0..topology.cell_count() {
+            let coord = topology.coord(idx);
+            assert_eq!(topology.index(&coord), Some(idx));
+            assert_eq!(topology.distance(&coord, &coord), 0);
+
+            for direction in topology.directions(&coord) {
+                let wall = topology.wall(&coord, direction);
+                let neighbour = match topology.neighbour(&coord, direction) {
+                    Some(neighbour) => neighbour,
+                    None => {
+                        assert!(!wall.removable(), "{} of {}", direction, coord);
+                        assert_eq!(topology.edge_index(&wall), None);
+                        continue;
+                    }
+                };
+
+                assert!(topology.index(&neighbour).is_some());
+                assert!(topology.distance(&coord, &neighbour) <= 1);
+                let shared = topology
+                    .directions(&neighbour)
+                    .into_iter()
+                    .filter(|d| topology.neighbour(&neighbour, *d) == Some(coord))
+                    .any(|d| topology.wall(&neighbour, d) == wall);
+                assert!(shared, "{} of {} isn't shared", direction, coord);
+
+                let edge = topology.edge_index(&wall).unwrap();
+                assert!(edge < topology.edge_count());
+                assert_eq!(*edges.entry(edge).or_insert(wall), wall);
+            }
+        }
+
+        assert_eq!(topology.edges().len(), edges.len());
+    }
+
+    /// The number of cells next to a cell
+    fn neighbour_count(topology: &dyn Topology, coord: Coord) -> usize {
+        topology
+            .directions(&coord)
+            .into_iter()
+            .filter(|d| topology.neighbour(&coord, *d).is_some())
+            .count()
+    }
+
+    #[test]
+    fn hex_cells() {
+        let hex = Hex::new(5, 4);
+        assert_consistent(&hex);
+        assert_eq!(neighbour_count(&hex, [2, 1].into()), 6);
+        assert_eq!(neighbour_count(&hex, [0, 0].into()), 2);
+        // Odd columns are shifted down
+        assert_eq!(
+            hex.neighbour(&[1, 0].into(), Direction::NorthEast),
+            Some([2, 0].into())
+        );
+        assert_eq!(
+            hex.neighbour(&[2, 1].into(), Direction::NorthEast),
+            Some([3, 0].into())
+        );
+    }
+
+    #[test]
+    fn delta_cells() {
+        let delta = Delta::new(6, 4);
+        assert_consistent(&delta);
+        assert!(Delta::points_up(&[0, 0].into()) != Delta::points_up(&[1, 0].into()));
+        assert!(Delta::points_up(&[0, 0].into()) != Delta::points_up(&[0, 1].into()));
+        for idx in 0..delta.cell_count() {
+            assert_eq!(delta.directions(&delta.coord(idx)).len(), 3);
+        }
+    }
+
+    #[test]
+    fn polar_cells() {
+        let polar = Polar::new(5);
+        assert_consistent(&polar);
+        // The centre only has outward neighbours, one per cell of the first ring
+        let centre = [0, 0].into();
+        let first_ring = (0..polar.cell_count())
+            .filter(|&idx| polar.coord(idx).y == 1)
+            .count();
+        assert_eq!(neighbour_count(&polar, centre), first_ring);
+        assert_eq!(polar.neighbour(&centre, Direction::Inward), None);
+
+        let (start, end) = polar.entrances().unwrap();
+        assert!(polar.index(&start).is_some() && polar.index(&end).is_some());
+        assert!(start != end);
+    }
+
+    #[test]
+    fn wrapping_grids() {
+        let plain = Grid::new(4, 3);
+        let cylinder = Grid::wrapping(4, 3, Wrap::Cylinder);
+        let torus = Grid::wrapping(4, 3, Wrap::Torus);
+        for grid in &[plain, cylinder, torus] {
+            assert_consistent(grid);
+        }
+
+        let corner = [0, 0].into();
+        assert_eq!(plain.neighbour(&corner, Direction::West), None);
+        assert_eq!(
+            cylinder.neighbour(&corner, Direction::West),
+            Some([3, 0].into())
+        );
+        assert_eq!(cylinder.neighbour(&corner, Direction::North), None);
+        assert_eq!(
+            torus.neighbour(&corner, Direction::North),
+            Some([0, 2].into())
+        );
+    }
+
+    #[test]
+    fn grid_floors() {
+        let grid = Grid::new(3, 3).with_depth(3);
+        assert_consistent(&grid);
+        assert_eq!(grid.cell_count(), 27);
+        assert_eq!(grid.coord(9), [0, 0, 1].into());
+        assert_eq!(
+            grid.neighbour(&[1, 1, 0].into(), Direction::Up),
+            Some([1, 1, 1].into())
+        );
+        assert_eq!(grid.neighbour(&[1, 1, 0].into(), Direction::Down), None);
+        assert_eq!(grid.neighbour(&[1, 1, 2].into(), Direction::Up), None);
+        // A single floor has no stairs
+        assert_eq!(
+            Grid::new(3, 3).neighbour(&[1, 1].into(), Direction::Up),
+            None
+        );
+    }
+
+    #[test]
+    fn weave_cells() {
+        let weave = Weave::new(5, 5);
+        assert_consistent(&weave);
+        let centre = [2, 2].into();
+        assert_eq!(
+            weave.neighbour(&centre, Direction::UnderEast),
+            Some([4, 2].into())
+        );
+        assert_eq!(
+            weave.neighbour(&centre, Direction::UnderNorth),
+            Some([2, 0].into())
+        );
+        assert_eq!(weave.neighbour(&[3, 2].into(), Direction::UnderEast), None);
+    }
+
+    #[test]
+    fn masked_cells() {
+        let mask = Mask::from_ascii("XXX\nX X\nXXX\n").unwrap();
+        let masked = Masked::new(&mask).unwrap();
+        assert_consistent(&masked);
+        assert_eq!(masked.cell_count(), 8);
+        assert_eq!(masked.index(&[1, 1].into()), None);
+        assert_eq!(masked.neighbour(&[1, 0].into(), Direction::South), None);
+    }
+
+    #[test]
+    fn upsilon_cells() {
+        let upsilon = Upsilon::new(5, 5);
+        assert_consistent(&upsilon);
+        assert!(Upsilon::is_octagon(&[2, 2].into()));
+        assert_eq!(neighbour_count(&upsilon, [2, 2].into()), 8);
+        assert_eq!(neighbour_count(&upsilon, [2, 1].into()), 4);
+    }
+
+    #[test]
+    fn voronoi_cells() {
+        let layout = |seed| Voronoi::new(8, 6, &mut Random::new(Seed(seed), Stream::Layout));
+        let voronoi = layout(1);
+        assert_consistent(&voronoi);
+
+        let neighbours = |voronoi: &Voronoi| -> Vec<Vec<Coord>> {
+            (0..voronoi.cell_count())
+                .map(|idx| {
+                    let coord = voronoi.coord(idx);
+                    voronoi
+                        .directions(&coord)
+                        .into_iter()
+                        .filter_map(|d| voronoi.neighbour(&coord, d))
+                        .collect()
+                })
+                .collect()
+        };
+        assert!(neighbours(&voronoi).iter().all(|n| !n.is_empty()));
+        // The layout only depends on the seed
+        assert_eq!(neighbours(&voronoi), neighbours(&layout(1)));
+        assert!(neighbours(&voronoi) != neighbours(&layout(2)));
+    }
+}
@@ -0,0 +1,273 @@
+use std::time::{Duration, Instant};
+
+use ggez::event::{KeyCode, KeyMods};
+use ggez::*;
+
+use config::Config;
+use maze::render::{Visualization, COLOR_BACKGROUND};
+use maze::{Budget, Generator, Maze, Random, Registry, Result, Seed, Solver, Stream};
+
+#[derive(Default)]
+struct Timer {
+    start: Option<Instant>,
+    end: Option<Instant>,
+}
+
+impl Timer {
+    fn is_running(&self) -> bool {
+        self.start.is_some() && self.end.is_none()
+    }
+
+    fn is_stopped(&self) -> bool {
+        !self.is_running()
+    }
+
+    fn start(&mut self) {
+        self.start = Some(Instant::now());
+    }
+
+    fn stop(&mut self) {
+        self.end = Some(Instant::now());
+    }
+
+    fn restart(&mut self) {
+        self.reset();
+        self.start();
+    }
+
+    fn reset(&mut self) {
+        self.start = None;
+        self.end = None;
+    }
+
+    fn duration(&self) -> Duration {
+        let start = self.start.expect("Timer not running");
+
+        match self.end {
+            None => start.elapsed(),
+            Some(end) => end.duration_since(start),
+        }
+    }
+}
+
+enum AppMode {
+    Generating,
+    Solving,
+}
+
+struct MainState<'a> {
+    maze: Maze,
+    visualization: Visualization,
+    mode: AppMode,
+
+    generator: Box<dyn Generator>,
+    solver: Box<dyn Solver>,
+
+    fps_timer: Timer,
+    gen_timer: Timer,
+    solve_timer: Timer,
+
+    config: &'a Config,
+    random: Random,
+    paused: bool,
+    /// The floor being shown
+    floor: i32,
+}
+
+impl<'a> MainState<'a> {
+    fn new(config: &'a Config) -> Result<MainState<'a>> {
+        let seed = config.seed().unwrap_or_else(Seed::random);
+        println!("Seed: {}", seed);
+
+        let mut placement = Random::new(seed, Stream::Placement);
+        let mut random = Random::new(seed, Stream::Generation);
+        let maze = config.maze_builder(seed)?.build(&mut placement)?;
+        let registry = Registry::default();
+        let generator = registry.generator(config.generator())?.init(
+            &maze,
+            &mut random,
+            &config.generator_params(),
+        )?;
+        let solver = registry
+            .solver(config.solver())?
+            .init(&maze, &config.solver_params())?;
+
+        let mut fps_timer = Timer::default();
+        fps_timer.start();
+
+        let floor = maze.start.z;
+        Ok(MainState {
+            maze,
+            visualization: Visualization::default(),
+            mode: AppMode::Generating,
+
+            generator,
+            solver,
+
+            fps_timer,
+            gen_timer: Timer::default(),
+            solve_timer: Timer::default(),
+
+            config,
+            random,
+            paused: false,
+            floor,
+        })
+    }
+
+    fn tick_gen(&mut self) -> Result<()> {
+        if self.gen_timer.is_stopped() {
+            self.gen_timer.start();
+        }
+
+        if !self.config.interactive_gen() {
+            self.generator.run_for(
+                &mut self.maze,
+                &mut self.random,
+                &mut self.visualization,
+                Budget::Unlimited,
+            )?;
+            self.gen_timer.stop();
+        }
+
+        if self.generator.is_done() {
+            if self.gen_timer.is_running() {
+                self.gen_timer.stop();
+                println!("Gen time: {} seconds", self.gen_timer.duration().as_secs());
+            }
+
+            self.visualization.clear();
+            self.mode = AppMode::Solving;
+        } else {
+            self.generator
+                .tick(&mut self.maze, &mut self.random, &mut self.visualization)?;
+        }
+        Ok(())
+    }
+
+    fn tick_solve(&mut self) -> Result<()> {
+        if self.solve_timer.is_stopped() {
+            self.solve_timer.start();
+        }
+
+        if !self.config.interactive_solve() {
+            self.solver
+                .run_for(&self.maze, &mut self.visualization, Budget::Unlimited)?;
+            self.solve_timer.stop();
+        }
+
+        if !self.solver.is_done() {
+            self.solver.tick(&self.maze, &mut self.visualization)?;
+        } else if self.solve_timer.is_running() {
+            self.solve_timer.stop();
+            println!(
+                "Solve time: {} seconds",
+                self.solve_timer.duration().as_secs()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn change_floor(&mut self, by: i32) {
+        let depth = self.maze.topology().depth() as i32;
+        let floor = (self.floor + by).max(0).min(depth - 1);
+        if floor != self.floor {
+            self.floor = floor;
+            println!("Floor: {}", floor);
+        }
+    }
+}
+
+impl<'a> event::EventHandler for MainState<'a> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        if self.config.print_fps() && self.fps_timer.duration() > Duration::from_secs(1) {
+            println!("FPS: {}", timer::fps(ctx));
+            self.fps_timer.restart();
+        }
+        while timer::check_update_time(ctx, self.config.ups()) {
+            if self.paused {
+                return Ok(());
+            }
+
+            match self.mode {
+                AppMode::Generating => match self.tick_gen() {
+                    Ok(()) => {}
+                    Err(e) => {
+                        eprintln!("[ERROR] {}", e);
+                        self.paused = true;
+                    }
+                },
+                AppMode::Solving => match self.tick_solve() {
+                    Ok(()) => {}
+                    Err(e) => {
+                        eprintln!("[ERROR] {}", e);
+                        self.paused = true;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        graphics::clear(ctx, COLOR_BACKGROUND.into());
+
+        self.maze.render(
+            ctx,
+            &self.visualization,
+            self.config.cell_width(),
+            self.config.cell_height(),
+            self.floor,
+        )?;
+
+        graphics::present(ctx)?;
+        timer::yield_now();
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        match keycode {
+            KeyCode::PageUp => self.change_floor(1),
+            KeyCode::PageDown => self.change_floor(-1),
+            KeyCode::Escape => event::quit(ctx),
+            _ => {}
+        }
+    }
+}
+
+/// Generates and solves the maze in a window
+pub fn run(config: &Config) {
+    let mut state = match MainState::new(config) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            return;
+        }
+    };
+
+    let title = format!(
+        "Mazes! Generator: {} Solver: {}",
+        config.generator(),
+        config.solver()
+    );
+    let (width, height) = state.maze.topology().size();
+    let (mut ctx, mut event_loop) = ContextBuilder::new("maze", "João Delgado")
+        .window_setup(conf::WindowSetup::default().title(&title))
+        .window_mode(conf::WindowMode::default().dimensions(
+            (width * config.cell_width() as f32).ceil(),
+            (height * config.cell_height() as f32).ceil(),
+        ))
+        .build()
+        .expect("Error building context");
+
+    if let Err(e) = event::run(&mut ctx, &mut event_loop, &mut state) {
+        println!("[ERROR] {}", e);
+    }
+}
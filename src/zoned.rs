@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use error::{Error, Result};
+use event::{Event, Observer};
+use generator::{Generator, GeneratorType};
+use mask::Mask;
+use maze::{Coord, Maze, Wall};
+use random::Random;
+use topology::{Masked, Topology};
+
+/// How the cells of a maze are split into zones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZoneLayout {
+    /// `columns` x `rows` rectangles of about the same size
+    Rectangles(u32, u32),
+    /// One row of characters per row of cells, cells with the same character share a zone
+    Map(Vec<Vec<char>>),
+}
+
+impl ZoneLayout {
+    pub fn from_text(text: &str) -> ZoneLayout {
+        ZoneLayout::Map(text.lines().map(|line| line.chars().collect()).collect())
+    }
+
+    /// The zone of every cell of the maze, by index. Zones of a map are numbered in the order
+    /// their characters first appear.
+    fn labels(&self, maze: &Maze) -> Result<Vec<usize>> {
+        let width = maze.coords().map(|c| c.x + 1).max().unwrap_or(0);
+        let height = maze.coords().map(|c| c.y + 1).max().unwrap_or(0);
+        let mut chars = HashMap::new();
+
+        maze.coords()
+            .map(|c| match *self {
+                ZoneLayout::Rectangles(columns, rows) => {
+                    // Zones are at least a cell wide and high
+                    let columns = u64::from(columns.min(width as u32));
+                    let rows = u64::from(rows.min(height as u32));
+                    let column = c.x as u64 * columns / width as u64;
+                    let row = c.y as u64 * rows / height as u64;
+                    Ok((row * columns + column) as usize)
+                }
+                ZoneLayout::Map(ref lines) => {
+                    let label = lines
+                        .get(c.y as usize)
+                        .and_then(|line| line.get(c.x as usize))
+                        .filter(|label| !label.is_whitespace())
+                        .ok_or_else(|| Error::InvalidParam("map".to_string(), c.to_string()))?;
+                    let next = chars.len();
+                    Ok(*chars.entry(*label).or_insert(next))
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for ZoneLayout {
+    type Err = Error;
+
+    /// `quadrants`, `halves` or `<columns>x<rows>`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidParam("zones".to_string(), s.to_string());
+        match s {
+            "quadrants" => return Ok(ZoneLayout::Rectangles(2, 2)),
+            "halves" => return Ok(ZoneLayout::Rectangles(2, 1)),
+            _ => {}
+        }
+
+        let mut parts = s.splitn(2, 'x').map(|part| part.parse::<u32>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(columns)), Some(Ok(rows))) if columns > 0 && rows > 0 => {
+                Ok(ZoneLayout::Rectangles(columns, rows))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A part of the maze generated on its own
+struct Zone {
+    maze: Maze,
+    /// Where the top left corner of the zone is in the whole maze
+    offset: Coord,
+    generator: Box<dyn Generator>,
+}
+
+fn shift(coord: Coord, offset: Coord) -> Coord {
+    Coord {
+        x: coord.x + offset.x,
+        y: coord.y + offset.y,
+        z: coord.z + offset.z,
+    }
+}
+
+impl Zone {
+    /// Makes the walls of the zone in the whole maze the same as in the zone
+    fn copy_to(&self, maze: &mut Maze) -> Result<()> {
+        for coord in self.maze.coords() {
+            let connected = self.maze.connected_neighbours(&coord);
+            for (neighbour, _) in self.maze.neighbours(&coord) {
+                let open = connected.iter().any(|(c, _)| *c == neighbour);
+                let (c1, c2) = (shift(coord, self.offset), shift(neighbour, self.offset));
+                let open_in_maze = maze.connected_neighbours(&c1).iter().any(|(c, _)| *c == c2);
+
+                if open && !open_in_maze {
+                    maze.link(&c1, &c2)?;
+                } else if !open && open_in_maze {
+                    maze.unlink(&c1, &c2)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a maze of square cells into zones, generates every zone with its own generator and
+/// then knocks down a few doors between every pair of neighbouring zones.
+///
+/// Zones of a layout that aren't in one piece are generated as several zones, with the same
+/// generator.
+pub struct Zoned {
+    zones: Vec<Zone>,
+    /// The zone being generated
+    current: usize,
+    /// The doors left to open once every zone is generated
+    doors: Vec<Wall>,
+}
+
+impl Zoned {
+    /// Every zone of the layout uses the generator at its position in `generators`, going back
+    /// to the first one when there are more zones than generators. `doors` doors are opened
+    /// between every pair of neighbouring zones, or as many as they have walls in between.
+    pub fn new(
+        maze: &Maze,
+        random: &mut Random,
+        layout: &ZoneLayout,
+        generators: &[GeneratorType],
+        doors: usize,
+    ) -> Result<Zoned> {
+        if !maze.has_square_cells() {
+            return Err(Error::UnsupportedShape("zoned".to_string()));
+        }
+        if generators.is_empty() {
+            return Err(Error::InvalidParam("generators".to_string(), String::new()));
+        }
+        // Zones without doors would be cut off from each other
+        if doors == 0 {
+            return Err(Error::InvalidParam("doors".to_string(), doors.to_string()));
+        }
+
+        let labels = layout.labels(maze)?;
+        let pieces = Zoned::pieces(maze, &labels);
+
+        let mut cells: Vec<Vec<Coord>> = vec![];
+        let mut piece_labels = vec![];
+        for coord in maze.coords() {
+            let idx = maze.topology().index(&coord).unwrap();
+            if pieces[idx] == cells.len() {
+                cells.push(vec![]);
+                piece_labels.push(labels[idx]);
+            }
+            cells[pieces[idx]].push(coord);
+        }
+
+        let mut zones = Vec::with_capacity(cells.len());
+        for (coords, label) in cells.iter().zip(piece_labels) {
+            let generator = generators[label % generators.len()];
+            zones.push(Zoned::zone(coords, generator, random)?);
+        }
+
+        // Walls between two zones, grouped by the zones they separate
+        let mut between: BTreeMap<(usize, usize), Vec<Wall>> = BTreeMap::new();
+        for wall in maze.topology().edges() {
+            let (c1, c2) = wall.divided_coords();
+            let z1 = pieces[maze.topology().index(&c1).unwrap()];
+            let z2 = pieces[maze.topology().index(&c2).unwrap()];
+            if z1 != z2 {
+                between
+                    .entry((z1.min(z2), z1.max(z2)))
+                    .or_default()
+                    .push(wall);
+            }
+        }
+
+        let mut chosen = vec![];
+        for walls in between.values_mut() {
+            random.shuffle(walls);
+            chosen.extend(walls.iter().take(doors).cloned());
+        }
+        // Doors are opened from the end
+        chosen.reverse();
+
+        Ok(Zoned {
+            zones,
+            current: 0,
+            doors: chosen,
+        })
+    }
+
+    /// The piece of every cell, by index: cells with the same label that can reach each other
+    /// without leaving the label or wrapping around the maze
+    fn pieces(maze: &Maze, labels: &[usize]) -> Vec<usize> {
+        let index = |coord: &Coord| maze.topology().index(coord).unwrap();
+        let mut pieces = vec![usize::MAX; labels.len()];
+        let mut count = 0;
+
+        for coord in maze.coords() {
+            if pieces[index(&coord)] != usize::MAX {
+                continue;
+            }
+
+            let mut pending = vec![coord];
+            pieces[index(&coord)] = count;
+            while let Some(current) = pending.pop() {
+                for (neighbour, direction) in maze.neighbours(&current) {
+                    let idx = index(&neighbour);
+                    let wrapped = neighbour != current.step(direction);
+                    if !wrapped
+                        && pieces[idx] == usize::MAX
+                        && labels[idx] == labels[index(&current)]
+                    {
+                        pieces[idx] = count;
+                        pending.push(neighbour);
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        pieces
+    }
+
+    fn zone(coords: &[Coord], generator: GeneratorType, random: &mut Random) -> Result<Zone> {
+        let min_x = coords.iter().map(|c| c.x).min().unwrap();
+        let min_y = coords.iter().map(|c| c.y).min().unwrap();
+        let max_x = coords.iter().map(|c| c.x).max().unwrap();
+        let max_y = coords.iter().map(|c| c.y).max().unwrap();
+        let offset = Coord {
+            x: min_x,
+            y: min_y,
+            z: coords[0].z,
+        };
+
+        let cells: HashSet<_> = coords.iter().collect();
+        let mut mask = Mask::new((max_x - min_x + 1) as u32, (max_y - min_y + 1) as u32);
+        for y in 0..mask.height() as i32 {
+            for x in 0..mask.width() as i32 {
+                let cell = Coord {
+                    x: x + min_x,
+                    y: y + min_y,
+                    z: offset.z,
+                };
+                mask.set([x, y].into(), cells.contains(&cell));
+            }
+        }
+
+        let topology: Arc<dyn Topology> = Arc::new(Masked::new(&mask)?);
+        let maze = Maze::with_topology(topology);
+        let generator = generator.init(&maze, random)?;
+
+        Ok(Zone {
+            maze,
+            offset,
+            generator,
+        })
+    }
+}
+
+impl Generator for Zoned {
+    fn is_done(&self) -> bool {
+        self.current >= self.zones.len() && self.doors.is_empty()
+    }
+
+    fn tick(
+        &mut self,
+        maze: &mut Maze,
+        random: &mut Random,
+        observer: &mut dyn Observer,
+    ) -> Result<()> {
+        if let Some(zone) = self.zones.get_mut(self.current) {
+            if zone.generator.is_done() {
+                zone.copy_to(maze)?;
+                self.current += 1;
+                return Ok(());
+            }
+
+            let offset = zone.offset;
+            // Passages carved or walled up, in order
+            let mut changes = vec![];
+            {
+                let mut forward = |event: Event| {
+                    let event = event.map_coords(|coord| shift(coord, offset));
+                    match event {
+                        Event::PassageCarved(c1, c2) => changes.push((c1, c2, true)),
+                        Event::WallAdded(c1, c2) => changes.push((c1, c2, false)),
+                        _ => {}
+                    }
+                    observer.notify(event);
+                };
+                zone.generator.tick(&mut zone.maze, random, &mut forward)?;
+            }
+
+            for (c1, c2, open) in changes {
+                if open {
+                    maze.link(&c1, &c2)?;
+                } else {
+                    maze.unlink(&c1, &c2)?;
+                }
+            }
+            return Ok(());
+        }
+
+        if let Some(door) = self.doors.pop() {
+            let (c1, c2) = door.divided_coords();
+            maze.link(&c1, &c2)?;
+            observer.notify(Event::Current(vec![c1, c2]));
+            observer.notify(Event::PassageCarved(c1, c2));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_layouts() {
+        assert_eq!(
+            "quadrants".parse::<ZoneLayout>().unwrap(),
+            ZoneLayout::Rectangles(2, 2)
+        );
+        assert_eq!(
+            "halves".parse::<ZoneLayout>().unwrap(),
+            ZoneLayout::Rectangles(2, 1)
+        );
+        assert_eq!(
+            "3x1".parse::<ZoneLayout>().unwrap(),
+            ZoneLayout::Rectangles(3, 1)
+        );
+    }
+
+    #[test]
+    fn invalid_layouts() {
+        for s in &["", "thirds", "3", "3x", "x2", "0x2", "3x0", "3x2x1", "-1x2"] {
+            match s.parse::<ZoneLayout>() {
+                Err(Error::InvalidParam(ref name, ref value)) => {
+                    assert_eq!((name.as_str(), value.as_str()), ("zones", *s))
+                }
+                other => panic!("{:?} parsed as {:?}", s, other),
+            }
+        }
+    }
+
+    #[test]
+    fn rectangle_labels() {
+        let maze = Maze::new(4, 2);
+        let halves = ZoneLayout::Rectangles(2, 1);
+        assert_eq!(halves.labels(&maze).unwrap(), vec![0, 0, 1, 1, 0, 0, 1, 1]);
+
+        // More zones than cells leaves a cell per zone
+        let huge = "3000000000x4000000000".parse::<ZoneLayout>().unwrap();
+        assert_eq!(huge.labels(&maze).unwrap(), (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn map_labels() {
+        let maze = Maze::new(3, 2);
+        let layout = ZoneLayout::from_text("bba\nccb\n");
+        assert_eq!(layout.labels(&maze).unwrap(), vec![0, 0, 1, 2, 2, 0]);
+
+        // Every cell needs a zone
+        let short = ZoneLayout::from_text("bba\ncc\n");
+        assert!(short.labels(&maze).is_err());
+    }
+}
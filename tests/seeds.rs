@@ -0,0 +1,181 @@
+extern crate maze;
+
+use maze::{generate, GeneratorType, MazeBuilder, Random, Seed, Stream};
+
+/// Every generator's maze for `Seed(42)`. These have to stay the same on every platform and
+/// across versions, as old seeds are expected to give back the same maze.
+const SNAPSHOTS: &[(&str, &str)] = &[
+    (
+        "dfs",
+        "\
++---+---+---+---+---+---+
+|   | E                 |
++   +---+   +---+   +   +
+|       |       |   |   |
++   +   +   +---+   +   +
+|   |   |   |       |   |
++   +   +   +   +---+---+
+|   |       |         S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "kruskal",
+        "\
++---+---+---+---+---+---+
+|   | E         |       |
++   +---+   +   +   +---+
+|   |       |   |       |
++   +---+   +---+   +   +
+|               |   |   |
++---+   +---+   +   +---+
+|           |         S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "prim",
+        "\
++---+---+---+---+---+---+
+|     E |   |   |   |   |
++---+   +   +   +   +   +
+|   |       |           |
++   +---+   +---+   +   +
+|   |       |       |   |
++   +   +---+---+   +---+
+|                     S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "eller",
+        "\
++---+---+---+---+---+---+
+|   | E     |   |       |
++   +   +---+   +---+   +
+|           |   |       |
++   +---+---+   +---+   +
+|   |       |       |   |
++   +---+   +   +---+   +
+|                     S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "hunt-kill",
+        "\
++---+---+---+---+---+---+
+|   | E         |       |
++   +   +---+   +   +   +
+|           |   |   |   |
++---+---+   +---+   +   +
+|       |           |   |
++   +   +---+---+---+   +
+|   |                 S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "wilson",
+        "\
++---+---+---+---+---+---+
+|   | E |               |
++   +   +   +   +---+   +
+|   |       |   |   |   |
++   +---+   +---+   +   +
+|                   |   |
++   +   +   +---+---+   +
+|   |   |       |     S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "aldous-broder",
+        "\
++---+---+---+---+---+---+
+|     E     |       |   |
++---+   +---+   +---+   +
+|                       |
++   +---+---+---+   +   +
+|       |   |       |   |
++   +   +   +---+---+   +
+|   |       |         S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "recursive-division",
+        "\
++---+---+---+---+---+---+
+|     E |       |       |
++---+   +   +---+   +---+
+|   |   |   |       |   |
++   +   +   +   +   +   +
+|               |       |
++---+---+   +---+---+   +
+|               |     S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "binary-tree",
+        "\
++---+---+---+---+---+---+
+|     E                 |
++---+   +---+---+   +   +
+|       |           |   |
++   +---+---+   +   +   +
+|   |           |   |   |
++---+---+   +   +---+   +
+|           |   |     S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "sidewinder",
+        "\
++---+---+---+---+---+---+
+|     E                 |
++   +   +---+---+---+   +
+|   |           |       |
++   +---+---+---+---+   +
+|               |       |
++---+   +   +   +---+---+
+|       |   |         S |
++---+---+---+---+---+---+
+",
+    ),
+    (
+        "growing-tree",
+        "\
++---+---+---+---+---+---+
+|     E                 |
++   +   +---+---+---+   +
+|   |               |   |
++   +---+---+---+   +---+
+|               |       |
++---+---+---+   +---+   +
+|                   | S |
++---+---+---+---+---+---+
+",
+    ),
+];
+
+fn generated(generator: GeneratorType) -> String {
+    let seed = Seed(42);
+    let mut maze = MazeBuilder::new(6, 4)
+        .build(&mut Random::new(seed, Stream::Placement))
+        .unwrap();
+    let mut random = Random::new(seed, Stream::Generation);
+    generate(&mut maze, generator, &mut random).unwrap();
+    maze.to_ascii(&[]).unwrap()
+}
+
+#[test]
+fn seeds_give_the_same_mazes() {
+    assert_eq!(SNAPSHOTS.len(), GeneratorType::all().len());
+    for &(name, snapshot) in SNAPSHOTS {
+        let generator = name.parse().unwrap();
+        assert_eq!(generated(generator), snapshot, "{}", name);
+    }
+}
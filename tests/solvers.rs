@@ -0,0 +1,44 @@
+extern crate maze;
+
+use std::sync::Arc;
+
+use maze::{
+    solve, Generator, GeneratorType, Grid, Maze, MazeBuilder, Random, Seed, SolverType, Stream,
+    ZoneLayout, Zoned,
+};
+
+/// A maze with loops, from zones joined by two doors each
+fn zoned(seed: u64) -> Maze {
+    let mut maze = MazeBuilder::with_topology(Arc::new(Grid::new(12, 10)))
+        .build(&mut Random::new(Seed(seed), Stream::Placement))
+        .unwrap();
+    let mut random = Random::new(Seed(seed), Stream::Generation);
+    let generators = [GeneratorType::DFS, GeneratorType::Kruskal];
+    let layout = ZoneLayout::Rectangles(3, 2);
+    let mut zoned = Zoned::new(&maze, &mut random, &layout, &generators, 2).unwrap();
+    while !zoned.is_done() {
+        zoned.tick(&mut maze, &mut random, &mut ()).unwrap();
+    }
+
+    maze
+}
+
+#[test]
+fn shortest_paths_through_loops() {
+    for seed in 0..100 {
+        let maze = zoned(seed);
+        let shortest = solve(&maze, SolverType::BFS).unwrap().len();
+        for solver in &[SolverType::Dijkstra, SolverType::AStar] {
+            let solution = solve(&maze, *solver).unwrap();
+            assert_eq!(solution.path.first(), Some(&maze.start));
+            assert_eq!(solution.path.last(), Some(&maze.end));
+            assert_eq!(
+                solution.len(),
+                shortest,
+                "{} with seed {}",
+                solver.name(),
+                seed
+            );
+        }
+    }
+}
@@ -0,0 +1,145 @@
+extern crate maze;
+
+use std::sync::Arc;
+
+use maze::{
+    generate, Error, Generator, GeneratorType, Grid, Mask, Masked, Maze, MazeBuilder, Random, Seed,
+    Stream, Topology, TopologyType, Wrap, ZoneLayout, Zoned,
+};
+
+/// A maze of `topology` with the start and end placed from `seed`
+fn placed(topology: Arc<dyn Topology>, seed: u64) -> Maze {
+    MazeBuilder::with_topology(topology)
+        .build(&mut Random::new(Seed(seed), Stream::Placement))
+        .unwrap()
+}
+
+/// Generates a maze of `topology`, or `None` if the generator doesn't support its shape
+fn generated(topology: Arc<dyn Topology>, generator: GeneratorType, seed: u64) -> Option<Maze> {
+    let mut maze = placed(topology, seed);
+    let mut random = Random::new(Seed(seed), Stream::Generation);
+    match generate(&mut maze, generator, &mut random) {
+        Ok(()) => Some(maze),
+        Err(Error::UnsupportedShape(_)) => None,
+        Err(e) => panic!("{} failed with seed {}: {}", generator.name(), seed, e),
+    }
+}
+
+/// The number of cells that can't be reached from the start
+fn unreachable_cells(maze: &Maze) -> usize {
+    let topology = maze.topology();
+    let mut reached = vec![false; maze.cell_count()];
+    let mut stack = vec![maze.start];
+    reached[topology.index(&maze.start).unwrap()] = true;
+    while let Some(coord) = stack.pop() {
+        for (neighbour, _) in maze.connected_neighbours(&coord) {
+            let idx = topology.index(&neighbour).unwrap();
+            if !reached[idx] {
+                reached[idx] = true;
+                stack.push(neighbour);
+            }
+        }
+    }
+
+    reached.iter().filter(|reached| !**reached).count()
+}
+
+/// Checks that every cell can be reached from the start through exactly one path
+fn assert_spanning_tree(maze: &Maze, context: &str) {
+    let passages = maze
+        .topology()
+        .edges()
+        .iter()
+        .filter(|wall| !maze.has_wall(wall))
+        .count();
+    assert_eq!(passages, maze.cell_count() - 1, "passages of {}", context);
+    assert_eq!(
+        unreachable_cells(maze),
+        0,
+        "unreachable cells of {}",
+        context
+    );
+}
+
+fn topologies() -> Vec<(String, Arc<dyn Topology>)> {
+    let mut layout = Random::new(Seed(0), Stream::Layout);
+    let mut topologies = vec![];
+    for topology in TopologyType::all().iter() {
+        let shape = topology.init(12, 10, 1, Wrap::None, &mut layout).unwrap();
+        topologies.push((topology.name().to_string(), shape));
+    }
+    for wrap in &[Wrap::Cylinder, Wrap::Torus] {
+        let shape = TopologyType::Grid
+            .init(12, 10, 1, *wrap, &mut layout)
+            .unwrap();
+        topologies.push((format!("grid {:?}", wrap), shape));
+    }
+    let floors = TopologyType::Grid
+        .init(6, 5, 3, Wrap::None, &mut layout)
+        .unwrap();
+    topologies.push(("grid with 3 floors".to_string(), floors));
+    let mask = Mask::from_ascii("XXXX\nXXXX\n   X\n").unwrap();
+    topologies.push(("mask".to_string(), Arc::new(Masked::new(&mask).unwrap())));
+
+    topologies
+}
+
+#[test]
+fn every_generator_builds_spanning_trees() {
+    for (name, topology) in topologies() {
+        for generator in GeneratorType::all().iter() {
+            for seed in 0..20 {
+                if let Some(maze) = generated(topology.clone(), *generator, seed) {
+                    let context = format!("{} on {} with seed {}", generator.name(), name, seed);
+                    assert_spanning_tree(&maze, &context);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn delta_corners_behind_the_end_are_reached() {
+    let mut layout = Random::new(Seed(0), Stream::Layout);
+    let topology = TopologyType::Delta
+        .init(12, 10, 1, Wrap::None, &mut layout)
+        .unwrap();
+    for seed in 0..50 {
+        let maze = generated(topology.clone(), GeneratorType::DFS, seed).unwrap();
+        assert_spanning_tree(&maze, &format!("seed {}", seed));
+    }
+}
+
+#[test]
+fn masked_mazes_are_spanning_trees() {
+    // The last cell hangs off the rest, so it's often behind the end
+    let mask = Mask::from_ascii("XXXX\nXXXX\n   X\n").unwrap();
+    let topology: Arc<dyn Topology> = Arc::new(Masked::new(&mask).unwrap());
+    for generator in GeneratorType::all().iter() {
+        for seed in 0..200 {
+            if let Some(maze) = generated(topology.clone(), *generator, seed) {
+                let context = format!("{} with seed {}", generator.name(), seed);
+                assert_spanning_tree(&maze, &context);
+            }
+        }
+    }
+}
+
+#[test]
+fn zones_of_a_map_are_all_reached() {
+    // The last cell of the `c` zone, where its maze ends, is the only way to the cell on its left.
+    // Doors between zones can make loops, so only the reachable cells are checked.
+    let layout = ZoneLayout::from_text("aaaa\naaac\naacc\naaaa\n");
+    for generator in &[GeneratorType::DFS, GeneratorType::GrowingTree] {
+        for seed in 0..50 {
+            let mut maze = placed(Arc::new(Grid::new(4, 4)), seed);
+            let mut random = Random::new(Seed(seed), Stream::Generation);
+            let mut zoned = Zoned::new(&maze, &mut random, &layout, &[*generator], 1).unwrap();
+            while !zoned.is_done() {
+                zoned.tick(&mut maze, &mut random, &mut ()).unwrap();
+            }
+            let context = format!("{} with seed {}", generator.name(), seed);
+            assert_eq!(unreachable_cells(&maze), 0, "{}", context);
+        }
+    }
+}